@@ -0,0 +1,127 @@
+//! Compares the old per-request `bounded(1)` response channel with the shared,
+//! sequence-keyed completion channel used by `WorkEnvelope`.
+//!
+//! Run with `cargo bench --bench completion`.
+
+use crossbeam_channel::{bounded, unbounded, Sender};
+use std::hint::black_box;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use supafast::models::{Completion, WorkEnvelope, WorkRequest, WorkResponse};
+use supafast::responses::ResponseCollector;
+
+const ITERATIONS: u64 = 1_000_000;
+const QUEUE_CAPACITY: usize = 1024;
+
+fn request(i: u64) -> WorkRequest {
+    WorkRequest::new(format!("req-{}", i))
+}
+
+// Allocate, send on and receive from a fresh channel per request
+fn per_request_channel() -> Duration {
+    let start = Instant::now();
+
+    for i in 0..ITERATIONS {
+        let (tx, rx) = bounded::<WorkResponse>(1);
+        let _ = tx.send(WorkResponse::success(String::new()));
+        black_box(rx.recv().ok());
+        black_box(i);
+    }
+
+    start.elapsed()
+}
+
+// Reuse one channel and tag each completion with its sequence number
+fn shared_completion_channel() -> Duration {
+    let (tx, rx) = unbounded::<Completion>();
+    let start = Instant::now();
+
+    for seq in 0..ITERATIONS {
+        let reply_to = tx.clone();
+        let _ = reply_to.send(Completion {
+            seq,
+            response: WorkResponse::success(String::new()),
+        });
+        black_box(rx.recv().ok());
+    }
+
+    start.elapsed()
+}
+
+// Old pipeline: generator -> worker thread, one response channel per request
+fn pipeline_per_request_channel() -> Duration {
+    let (work_tx, work_rx) = bounded::<(WorkRequest, Sender<WorkResponse>)>(QUEUE_CAPACITY);
+    let worker = thread::spawn(move || {
+        while let Ok((request, response_tx)) = work_rx.recv() {
+            let _ = response_tx.send(WorkResponse::success(request.id));
+        }
+    });
+
+    let start = Instant::now();
+    for i in 0..ITERATIONS {
+        let (response_tx, _response_rx) = bounded(1);
+        if work_tx.send((request(i), response_tx)).is_err() {
+            break;
+        }
+    }
+    drop(work_tx);
+    let _ = worker.join();
+
+    start.elapsed()
+}
+
+// New pipeline: generator -> worker thread -> response collector via envelopes
+fn pipeline_envelope() -> Duration {
+    let (work_tx, work_rx) = bounded::<WorkEnvelope>(QUEUE_CAPACITY);
+    let worker = thread::spawn(move || {
+        while let Ok(envelope) = work_rx.recv() {
+            envelope.complete(WorkResponse::success(envelope.request.id.clone()));
+        }
+    });
+
+    let start = Instant::now();
+    let collector = ResponseCollector::start(ITERATIONS);
+    let tracker = collector.tracker();
+    for i in 0..ITERATIONS {
+        if work_tx.send(tracker.envelope(request(i))).is_err() {
+            break;
+        }
+    }
+    drop(tracker);
+    drop(work_tx);
+    let _ = worker.join();
+    let report = collector.finish();
+    assert_eq!(report.matched, ITERATIONS);
+
+    start.elapsed()
+}
+
+fn print_comparison(name: &str, old: Duration, new: Duration) {
+    let per_op = |d: Duration| d.as_nanos() as f64 / ITERATIONS as f64;
+
+    println!("{}", name);
+    println!("  per-request channel: {:>10.1} ns/op", per_op(old));
+    println!("  shared completion:   {:>10.1} ns/op", per_op(new));
+    println!(
+        "  speedup:             {:>10.2}x\n",
+        old.as_secs_f64() / new.as_secs_f64()
+    );
+}
+
+fn main() {
+    println!("{} iterations\n", ITERATIONS);
+
+    print_comparison(
+        "Completion path (single thread)",
+        per_request_channel(),
+        shared_completion_channel(),
+    );
+
+    // The envelope pipeline also matches every completion, which the old one never did
+    print_comparison(
+        "Generator -> worker pipeline",
+        pipeline_per_request_channel(),
+        pipeline_envelope(),
+    );
+}
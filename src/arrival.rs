@@ -0,0 +1,193 @@
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArrivalProcess {
+    // Evenly spaced requests at exactly the pattern's rate
+    Uniform,
+    // Exponential inter-arrival times
+    Poisson,
+    // Each gap drawn uniformly within +/- `jitter` of the nominal interval
+    Jitter,
+    // Markov-modulated Poisson: exponential on/off periods, Poisson arrivals only while on
+    OnOff,
+}
+
+impl std::fmt::Display for ArrivalProcess {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArrivalProcess::Uniform => write!(f, "uniform"),
+            ArrivalProcess::Poisson => write!(f, "poisson"),
+            ArrivalProcess::Jitter => write!(f, "jitter"),
+            ArrivalProcess::OnOff => write!(f, "on_off"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ArrivalSettings {
+    pub process: ArrivalProcess,
+    // Fraction of the nominal interval, for `jitter`
+    pub jitter: f64,
+    // Mean on and off period lengths, for `on_off`
+    pub mean_on_ms: u64,
+    pub mean_off_ms: u64,
+}
+
+impl Default for ArrivalSettings {
+    fn default() -> Self {
+        Self {
+            process: ArrivalProcess::Uniform,
+            jitter: 0.2,
+            mean_on_ms: 1000,
+            mean_off_ms: 1000,
+        }
+    }
+}
+
+/// Draws inter-arrival gaps around the pattern's current rate, keeping its mean rate;
+/// the same RNG stream always yields the same gaps.
+pub struct ArrivalSampler {
+    settings: ArrivalSettings,
+    rng: ChaCha8Rng,
+    // On/off state: whether arrivals are currently on, and how long the state lasts
+    on: bool,
+    state_remaining: f64,
+}
+
+impl ArrivalSampler {
+    pub fn new(settings: ArrivalSettings, mut rng: ChaCha8Rng) -> Self {
+        let state_remaining = exponential(&mut rng, settings.mean_on_ms as f64 / 1000.0);
+
+        Self {
+            settings,
+            rng,
+            on: true,
+            state_remaining,
+        }
+    }
+
+    /// Returns the gap until the next request for a mean rate of `rps`.
+    pub fn next_gap(&mut self, rps: f64) -> Duration {
+        let mean = 1.0 / rps.max(f64::MIN_POSITIVE);
+
+        let gap = match self.settings.process {
+            ArrivalProcess::Uniform => mean,
+            ArrivalProcess::Poisson => exponential(&mut self.rng, mean),
+            ArrivalProcess::Jitter => {
+                let jitter = self.settings.jitter.clamp(0.0, 1.0);
+                mean * (1.0 + self.rng.random_range(-jitter..=jitter))
+            }
+            ArrivalProcess::OnOff => self.next_on_off_gap(mean),
+        };
+
+        Duration::from_secs_f64(gap.min(u32::MAX as f64))
+    }
+
+    fn next_on_off_gap(&mut self, mean: f64) -> f64 {
+        let mean_on = self.settings.mean_on_ms as f64 / 1000.0;
+        let mean_off = self.settings.mean_off_ms as f64 / 1000.0;
+        if mean_on <= 0.0 {
+            return exponential(&mut self.rng, mean);
+        }
+
+        // Compress the arrivals into the on periods so the overall mean rate holds
+        let on_mean = mean * mean_on / (mean_on + mean_off);
+        let mut gap = 0.0;
+
+        loop {
+            if self.on {
+                let next = exponential(&mut self.rng, on_mean);
+                if next <= self.state_remaining {
+                    self.state_remaining -= next;
+                    return gap + next;
+                }
+
+                // Exponential gaps are memoryless, so the draw can be discarded at the
+                // state change
+                gap += self.state_remaining;
+                self.on = false;
+                self.state_remaining = exponential(&mut self.rng, mean_off);
+            } else {
+                gap += self.state_remaining;
+                self.on = true;
+                self.state_remaining = exponential(&mut self.rng, mean_on);
+            }
+        }
+    }
+}
+
+// Inverse-CDF sample of an exponential distribution with the given mean
+fn exponential(rng: &mut ChaCha8Rng, mean: f64) -> f64 {
+    let u: f64 = rng.random();
+    -(1.0 - u).ln() * mean
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::seed::{RngStream, RunSeed};
+
+    const DRAWS: usize = 100_000;
+
+    fn sampler(process: ArrivalProcess, stream: u64) -> ArrivalSampler {
+        let settings = ArrivalSettings {
+            process,
+            jitter: 0.5,
+            // Short periods, so the draws span thousands of on/off cycles
+            mean_on_ms: 50,
+            mean_off_ms: 50,
+        };
+        ArrivalSampler::new(settings, RunSeed::new(42).rng(RngStream::Generator, stream))
+    }
+
+    fn mean_rate(process: ArrivalProcess, rps: f64) -> f64 {
+        let mut sampler = sampler(process, 0);
+        let total: f64 = (0..DRAWS)
+            .map(|_| sampler.next_gap(rps).as_secs_f64())
+            .sum();
+
+        DRAWS as f64 / total
+    }
+
+    #[test]
+    fn every_process_keeps_the_mean_rate() {
+        for process in [
+            ArrivalProcess::Uniform,
+            ArrivalProcess::Poisson,
+            ArrivalProcess::Jitter,
+            ArrivalProcess::OnOff,
+        ] {
+            let rate = mean_rate(process, 100.0);
+            assert!(
+                (rate - 100.0).abs() < 3.0,
+                "{} averaged {:.2} rps",
+                process,
+                rate
+            );
+        }
+    }
+
+    #[test]
+    fn same_seed_and_stream_give_the_same_gaps() {
+        for process in [
+            ArrivalProcess::Poisson,
+            ArrivalProcess::Jitter,
+            ArrivalProcess::OnOff,
+        ] {
+            let gaps = |stream| {
+                let mut sampler = sampler(process, stream);
+                (0..1000)
+                    .map(|_| sampler.next_gap(100.0))
+                    .collect::<Vec<_>>()
+            };
+
+            assert_eq!(gaps(0), gaps(0), "{}", process);
+            assert_ne!(gaps(0), gaps(1), "{}", process);
+        }
+    }
+}
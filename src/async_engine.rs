@@ -0,0 +1,158 @@
+use crossbeam_channel::Receiver;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+use tokio::sync::Semaphore;
+use tokio_postgres::{Client, NoTls, Statement};
+
+use crate::benchmark::BenchmarkCollector;
+use crate::error::EngineError;
+use crate::metrics::MetricsCollector;
+use crate::models::{ResponseStatus, WorkEnvelope, WorkRequest, WorkResponse};
+use crate::pool::{ConnectionSource, SET_CONFIG_SQL};
+use crate::settings::EngineSettings;
+use crate::workload::WorkloadKind;
+
+const INSERT_SQL: &str = "INSERT INTO your_table (id, created_at) VALUES ($1, NOW())";
+
+// A pipelined client shared by many in-flight requests
+struct AsyncConnection {
+    client: Client,
+    insert: Statement,
+}
+
+struct Recorder {
+    metrics: MetricsCollector,
+    benchmark: Option<BenchmarkCollector>,
+}
+
+impl Recorder {
+    #[inline]
+    fn record(&self, latency: std::time::Duration, response: &WorkResponse) {
+        let success = response.success;
+        if success {
+            self.metrics.record_success();
+            self.metrics.record_written(response.rows, response.bytes);
+        } else {
+            self.metrics.record_failure();
+        }
+
+        if let Some(ref benchmark) = self.benchmark {
+            benchmark.record_request(latency, success);
+        }
+    }
+}
+
+/// Serves the work queue as tasks over pipelined `tokio_postgres` clients, at most
+/// `max_in_flight` at once; the thread exits once the queue is closed and drained.
+pub fn spawn_async_engine(
+    connections: &ConnectionSource,
+    settings: &EngineSettings,
+    receiver: Receiver<WorkEnvelope>,
+    metrics: MetricsCollector,
+    benchmark: Option<BenchmarkCollector>,
+) -> Result<thread::JoinHandle<()>, EngineError> {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(settings.worker_threads.max(1))
+        .thread_name("async-engine")
+        .enable_all()
+        .build()?;
+
+    let clients = runtime.block_on(connect_all(connections, settings.connections.max(1)))?;
+
+    let max_in_flight = settings.max_in_flight.max(1);
+    let semaphore = Arc::new(Semaphore::new(max_in_flight));
+    let recorder = Arc::new(Recorder { metrics, benchmark });
+
+    let handle = thread::Builder::new()
+        .name("async-dispatch".to_string())
+        .spawn(move || {
+            println!("Async engine started");
+
+            let mut next = 0;
+            while let Ok(envelope) = receiver.recv() {
+                let permit = runtime
+                    .block_on(Arc::clone(&semaphore).acquire_owned())
+                    .expect("Async engine semaphore closed");
+
+                let conn = Arc::clone(&clients[next % clients.len()]);
+                next += 1;
+                let recorder = Arc::clone(&recorder);
+
+                runtime.spawn(async move {
+                    let start = Instant::now();
+                    let result = process_request(&conn, &envelope.request).await;
+                    recorder.record(start.elapsed(), &result);
+
+                    envelope.complete(result);
+                    drop(permit);
+                });
+            }
+
+            // Every permit back means every spawned request has finished
+            let _ = runtime.block_on(semaphore.acquire_many(max_in_flight as u32));
+
+            println!("Async engine shutting down");
+        })
+        .expect("Failed to spawn async dispatch thread");
+
+    Ok(handle)
+}
+
+async fn connect_all(
+    connections: &ConnectionSource,
+    count: usize,
+) -> Result<Vec<Arc<AsyncConnection>>, EngineError> {
+    let mut clients = Vec::with_capacity(count);
+
+    for _ in 0..count {
+        let (client, connection) =
+            tokio_postgres::connect(connections.database_url(), NoTls).await?;
+        tokio::spawn(async move {
+            if let Err(e) = connection.await {
+                eprintln!("Async engine connection error: {}", e);
+            }
+        });
+
+        let session = connections.session();
+        for (name, value) in session.settings() {
+            client.execute(SET_CONFIG_SQL, &[name, value]).await?;
+        }
+        for statement in session.init_sql() {
+            client.batch_execute(statement).await?;
+        }
+
+        let insert = client.prepare(INSERT_SQL).await?;
+        clients.push(Arc::new(AsyncConnection { client, insert }));
+    }
+
+    Ok(clients)
+}
+
+async fn process_request(conn: &AsyncConnection, request: &WorkRequest) -> WorkResponse {
+    // Only single-row inserts are prepared here, so other replayed operations are rejected
+    let other_operation = request
+        .operation
+        .is_some_and(|kind| kind != WorkloadKind::Insert);
+    if request.id.is_empty() || other_operation {
+        return WorkResponse::failure(request.id.clone(), ResponseStatus::Invalid);
+    }
+
+    let row_id = request.row_id();
+    match conn.client.execute(&conn.insert, &[&row_id]).await {
+        Ok(_) => WorkResponse::success(request.id.clone()).written(1, row_id.len() as u64),
+        Err(e) => {
+            if cfg!(debug_assertions) {
+                eprintln!("Async engine error: {}", e);
+            }
+
+            let status = if e.is_closed() {
+                ResponseStatus::ConnectionError
+            } else {
+                ResponseStatus::Failed
+            };
+
+            WorkResponse::failure(request.id.clone(), status)
+        }
+    }
+}
@@ -18,7 +18,7 @@ pub struct BenchmarkStats {
 }
 
 pub struct BenchmarkCollector {
-    start_time: Instant,
+    start_time: Arc<Mutex<Instant>>,
     latencies: Arc<Mutex<Vec<Duration>>>,
     total_requests: Arc<Mutex<u64>>,
     successful_requests: Arc<Mutex<u64>>,
@@ -28,7 +28,7 @@ pub struct BenchmarkCollector {
 impl BenchmarkCollector {
     pub fn new() -> Self {
         Self {
-            start_time: Instant::now(),
+            start_time: Arc::new(Mutex::new(Instant::now())),
             latencies: Arc::new(Mutex::new(Vec::with_capacity(10000))),
             total_requests: Arc::new(Mutex::new(0)),
             successful_requests: Arc::new(Mutex::new(0)),
@@ -50,7 +50,7 @@ impl BenchmarkCollector {
 
     pub fn get_stats(&self) -> BenchmarkStats {
         let mut latencies = self.latencies.lock().clone();
-        let total_duration = self.start_time.elapsed();
+        let total_duration = self.start_time.lock().elapsed();
         let total_requests = *self.total_requests.lock();
         let successful_requests = *self.successful_requests.lock();
         let failed_requests = *self.failed_requests.lock();
@@ -79,13 +79,9 @@ impl BenchmarkCollector {
             latencies.iter().map(|d| d.as_nanos() as u64).sum::<u64>() / latencies.len() as u64,
         );
 
-        let p50_idx = (latencies.len() as f64 * 0.50) as usize;
-        let p95_idx = (latencies.len() as f64 * 0.95) as usize;
-        let p99_idx = (latencies.len() as f64 * 0.99) as usize;
-
-        let p50_latency = latencies[p50_idx.min(latencies.len() - 1)];
-        let p95_latency = latencies[p95_idx.min(latencies.len() - 1)];
-        let p99_latency = latencies[p99_idx.min(latencies.len() - 1)];
+        let p50_latency = latency_percentile(&latencies, 0.50);
+        let p95_latency = latency_percentile(&latencies, 0.95);
+        let p99_latency = latency_percentile(&latencies, 0.99);
 
         let throughput_rps = if total_duration.as_secs_f64() > 0.0 {
             total_requests as f64 / total_duration.as_secs_f64()
@@ -109,6 +105,7 @@ impl BenchmarkCollector {
     }
 
     pub fn reset(&self) {
+        *self.start_time.lock() = Instant::now();
         self.latencies.lock().clear();
         *self.total_requests.lock() = 0;
         *self.successful_requests.lock() = 0;
@@ -117,7 +114,7 @@ impl BenchmarkCollector {
 
     pub fn clone_handle(&self) -> Self {
         Self {
-            start_time: self.start_time,
+            start_time: Arc::clone(&self.start_time),
             latencies: Arc::clone(&self.latencies),
             total_requests: Arc::clone(&self.total_requests),
             successful_requests: Arc::clone(&self.successful_requests),
@@ -132,6 +129,38 @@ impl Default for BenchmarkCollector {
     }
 }
 
+/// Nearest-rank percentile of an already sorted slice; zero when empty.
+pub fn latency_percentile(sorted: &[Duration], quantile: f64) -> Duration {
+    if sorted.is_empty() {
+        return Duration::ZERO;
+    }
+
+    let idx = (sorted.len() as f64 * quantile) as usize;
+    sorted[idx.min(sorted.len() - 1)]
+}
+
+pub fn print_warmup_report(stats: &BenchmarkStats) {
+    println!("\n🔥 Warmup (excluded from results):");
+    println!("  Requests:            {:>10}", stats.total_requests);
+    println!("  Failed:              {:>10}", stats.failed_requests);
+    println!(
+        "  Avg Latency:         {:>10.3} ms",
+        stats.avg_latency.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  P99 Latency:         {:>10.3} ms",
+        stats.p99_latency.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  Max Latency:         {:>10.3} ms",
+        stats.max_latency.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  Duration:            {:>10.3} s",
+        stats.total_duration.as_secs_f64()
+    );
+}
+
 pub fn print_benchmark_report(stats: &BenchmarkStats) {
     println!("\n{}", "=".repeat(60));
     println!("PERFORMANCE BENCHMARK REPORT");
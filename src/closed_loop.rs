@@ -0,0 +1,167 @@
+use crossbeam_channel::Sender;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::benchmark::{BenchmarkCollector, BenchmarkStats};
+use crate::models::{WorkEnvelope, WorkRequest};
+use crate::shutdown::CancellationToken;
+
+#[derive(Debug, Clone)]
+pub struct ClosedLoopReport {
+    pub users: usize,
+    pub think_time: Duration,
+    // Round-trip latency as seen by the virtual users, across all of them
+    pub aggregate: BenchmarkStats,
+    pub per_user: Vec<BenchmarkStats>,
+}
+
+/// Drives the work queue with `users` virtual users, each waiting for its response and
+/// think time before the next send; each user gets an even, contiguous share of ids.
+pub fn run_virtual_users<F>(
+    users: usize,
+    total_requests: u64,
+    think_time: Duration,
+    work_sender: Sender<WorkEnvelope>,
+    cancel: CancellationToken,
+    request_factory: F,
+) -> ClosedLoopReport
+where
+    F: Fn(u64) -> WorkRequest + Send + Sync + 'static,
+{
+    let users = users.max(1);
+    let request_factory = Arc::new(request_factory);
+    let aggregate = BenchmarkCollector::new();
+
+    let base_quota = total_requests / users as u64;
+    let remainder = total_requests % users as u64;
+
+    let handles: Vec<_> = (0..users as u64)
+        .map(|user_id| {
+            let quota = base_quota + u64::from(user_id < remainder);
+            let first_id = user_id * base_quota + user_id.min(remainder);
+            let work_sender = work_sender.clone();
+            let request_factory = Arc::clone(&request_factory);
+            let aggregate = aggregate.clone_handle();
+            let cancel = cancel.clone();
+
+            thread::Builder::new()
+                .name(format!("vuser-{}", user_id))
+                .spawn(move || {
+                    let collector = BenchmarkCollector::new();
+                    let (response_tx, response_rx) = crossbeam_channel::bounded(1);
+
+                    for i in first_id..first_id + quota {
+                        if cancel.is_cancelled() {
+                            break;
+                        }
+
+                        let request = request_factory(i);
+                        let start = Instant::now();
+
+                        let envelope = WorkEnvelope::new(request, i, response_tx.clone());
+                        if work_sender.send(envelope).is_err() {
+                            break;
+                        }
+
+                        let Ok(completion) = response_rx.recv() else {
+                            break;
+                        };
+
+                        let latency = start.elapsed();
+                        let success = completion.response.success;
+                        collector.record_request(latency, success);
+                        aggregate.record_request(latency, success);
+
+                        // Think time ends early on Ctrl-C/SIGTERM
+                        if !cancel.sleep_until(Instant::now() + think_time) {
+                            break;
+                        }
+                    }
+
+                    collector.get_stats()
+                })
+                .expect("Failed to spawn virtual user thread")
+        })
+        .collect();
+
+    let per_user = handles
+        .into_iter()
+        .map(|handle| handle.join().expect("Virtual user panicked"))
+        .collect();
+
+    ClosedLoopReport {
+        users,
+        think_time,
+        aggregate: aggregate.get_stats(),
+        per_user,
+    }
+}
+
+pub fn print_closed_loop_report(report: &ClosedLoopReport) {
+    println!(
+        "\n👥 Closed Loop ({} users, {} ms think time):",
+        report.users,
+        report.think_time.as_millis()
+    );
+    println!(
+        "  {:>6} {:>10} {:>10} {:>10} {:>10}",
+        "User", "Requests", "Req/s", "Avg ms", "P99 ms"
+    );
+
+    for (user_id, stats) in report.per_user.iter().enumerate() {
+        println!(
+            "  {:>6} {:>10} {:>10.2} {:>10.3} {:>10.3}",
+            user_id,
+            stats.total_requests,
+            stats.throughput_rps,
+            stats.avg_latency.as_secs_f64() * 1000.0,
+            stats.p99_latency.as_secs_f64() * 1000.0,
+        );
+    }
+}
+
+/// Prints throughput and latency per user count and marks the knee, the point of
+/// maximum power (throughput over mean latency).
+pub fn print_user_sweep(label: &str, reports: &[&ClosedLoopReport]) {
+    if reports.is_empty() {
+        return;
+    }
+
+    let power = |report: &ClosedLoopReport| {
+        let avg = report.aggregate.avg_latency.as_secs_f64();
+        if avg > 0.0 {
+            report.aggregate.throughput_rps / avg
+        } else {
+            0.0
+        }
+    };
+
+    let knee = reports
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| power(a).total_cmp(&power(b)))
+        .map(|(i, _)| i);
+
+    println!("\n{}", "=".repeat(60));
+    println!("CLOSED LOOP USER SWEEP ({})", label);
+    println!("{}", "=".repeat(60));
+    println!(
+        "\n  {:>6} {:>10} {:>10} {:>10} {:>10}",
+        "Users", "Req/s", "Avg ms", "P99 ms", "Errors"
+    );
+
+    for (i, report) in reports.iter().enumerate() {
+        println!(
+            "  {:>6} {:>10.2} {:>10.3} {:>10.3} {:>10}{}",
+            report.users,
+            report.aggregate.throughput_rps,
+            report.aggregate.avg_latency.as_secs_f64() * 1000.0,
+            report.aggregate.p99_latency.as_secs_f64() * 1000.0,
+            report.aggregate.failed_requests,
+            if Some(i) == knee { "  ◀ knee" } else { "" }
+        );
+    }
+
+    println!("\n{}", "=".repeat(60));
+}
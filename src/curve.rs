@@ -0,0 +1,245 @@
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Interpolation {
+    // Rate changes linearly between neighbouring points
+    Linear,
+    // Each point's rate holds until the next point
+    Step,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CurveSettings {
+    pub interpolation: Interpolation,
+    // `[seconds, rps]` pairs; when non-empty they replace the load pattern
+    pub points: Vec<(f64, f64)>,
+}
+
+impl Default for CurveSettings {
+    fn default() -> Self {
+        Self {
+            interpolation: Interpolation::Linear,
+            points: Vec::new(),
+        }
+    }
+}
+
+/// A request rate over time, given as data; the first point's rate applies before it
+/// and the curve ends at the last point.
+pub struct RateCurve {
+    interpolation: Interpolation,
+    // Sorted by time, in seconds
+    points: Vec<(f64, f64)>,
+}
+
+impl RateCurve {
+    /// Returns None when there are no points.
+    pub fn new(settings: &CurveSettings) -> Option<Self> {
+        if settings.points.is_empty() {
+            return None;
+        }
+
+        let mut points: Vec<(f64, f64)> = settings
+            .points
+            .iter()
+            .map(|&(time, rps)| (time.max(0.0), rps.max(0.0)))
+            .collect();
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        Some(Self {
+            interpolation: settings.interpolation,
+            points,
+        })
+    }
+
+    pub fn duration(&self) -> Duration {
+        Duration::from_secs_f64(self.points[self.points.len() - 1].0)
+    }
+
+    /// Number of requests the curve sends over its whole duration.
+    pub fn expected_requests(&self) -> u64 {
+        let total: f64 = self.segments().map(|segment| segment.area()).sum();
+        total.round() as u64
+    }
+
+    /// Time at which `count` requests are due in total, following the curve exactly
+    /// through rate changes and zero-rate stretches; None once past the end.
+    pub fn time_at_count(&self, count: f64) -> Option<Duration> {
+        let mut due = 0.0;
+
+        for segment in self.segments() {
+            let area = segment.area();
+            if count < due + area {
+                let offset = segment.start + segment.time_for(count - due);
+                return Some(Duration::from_secs_f64(offset));
+            }
+            due += area;
+        }
+
+        None
+    }
+
+    // Spans between neighbouring points, led by the first point's rate from time zero
+    fn segments(&self) -> impl Iterator<Item = Segment> + '_ {
+        let (first_time, first_rate) = self.points[0];
+        let lead = Segment {
+            start: 0.0,
+            end: first_time,
+            start_rate: first_rate,
+            end_rate: first_rate,
+        };
+
+        let interpolation = self.interpolation;
+        let spans = self.points.windows(2).map(move |pair| {
+            let ((t0, r0), (t1, r1)) = (pair[0], pair[1]);
+            Segment {
+                start: t0,
+                end: t1,
+                start_rate: r0,
+                end_rate: match interpolation {
+                    Interpolation::Step => r0,
+                    Interpolation::Linear => r1,
+                },
+            }
+        });
+
+        std::iter::once(lead).chain(spans)
+    }
+}
+
+// A stretch of the curve over which the rate changes linearly (or not at all)
+struct Segment {
+    start: f64,
+    end: f64,
+    start_rate: f64,
+    end_rate: f64,
+}
+
+impl Segment {
+    // Requests sent over the whole segment
+    fn area(&self) -> f64 {
+        (self.start_rate + self.end_rate) / 2.0 * (self.end - self.start)
+    }
+
+    // Time into the segment by which `count` of its requests are due; solves
+    // `start_rate * x + slope * x^2 / 2 = count` in a form that also holds for a flat
+    // rate and for a rate starting at zero
+    fn time_for(&self, count: f64) -> f64 {
+        if count <= 0.0 {
+            return 0.0;
+        }
+
+        let slope = (self.end_rate - self.start_rate) / (self.end - self.start);
+        let root = (self.start_rate * self.start_rate + 2.0 * slope * count)
+            .max(0.0)
+            .sqrt();
+
+        (2.0 * count / (self.start_rate + root)).min(self.end - self.start)
+    }
+}
+
+impl fmt::Debug for RateCurve {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {:?} points over {:.1}s",
+            self.points.len(),
+            self.interpolation,
+            self.duration().as_secs_f64()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn curve(interpolation: Interpolation, points: &[(f64, f64)]) -> RateCurve {
+        RateCurve::new(&CurveSettings {
+            interpolation,
+            points: points.to_vec(),
+        })
+        .unwrap()
+    }
+
+    fn secs(secs: f64) -> Duration {
+        Duration::from_secs_f64(secs)
+    }
+
+    // When `count` requests are due, in seconds
+    fn due(curve: &RateCurve, count: f64) -> f64 {
+        curve.time_at_count(count).unwrap().as_secs_f64()
+    }
+
+    #[test]
+    fn linear_interpolates_between_points() {
+        let curve = curve(Interpolation::Linear, &[(0.0, 0.0), (10.0, 100.0)]);
+
+        // 5t^2 requests by t
+        assert_eq!(due(&curve, 20.0), 2.0);
+        assert_eq!(due(&curve, 125.0), 5.0);
+        assert_eq!(curve.expected_requests(), 500);
+    }
+
+    #[test]
+    fn step_holds_each_rate_until_the_next_point() {
+        let curve = curve(Interpolation::Step, &[(0.0, 10.0), (2.0, 50.0), (4.0, 0.0)]);
+
+        assert_eq!(due(&curve, 10.0), 1.0);
+        assert_eq!(due(&curve, 20.0), 2.0);
+        assert_eq!(due(&curve, 45.0), 2.5);
+        assert_eq!(curve.expected_requests(), 120);
+    }
+
+    #[test]
+    fn first_rate_applies_before_the_first_point() {
+        let curve = curve(Interpolation::Linear, &[(2.0, 100.0), (4.0, 100.0)]);
+
+        assert_eq!(due(&curve, 50.0), 0.5);
+        // Half the budget is due by the first point
+        assert_eq!(due(&curve, 200.0), 2.0);
+        assert_eq!(curve.expected_requests(), 400);
+    }
+
+    #[test]
+    fn duplicate_times_jump_between_rates() {
+        let curve = curve(
+            Interpolation::Linear,
+            &[(0.0, 10.0), (2.0, 10.0), (2.0, 50.0), (4.0, 50.0)],
+        );
+
+        assert_eq!(due(&curve, 10.0), 1.0);
+        assert_eq!(due(&curve, 20.0), 2.0);
+        assert_eq!(due(&curve, 45.0), 2.5);
+        assert_eq!(curve.expected_requests(), 120);
+    }
+
+    #[test]
+    fn single_point_is_a_constant_rate_up_to_it() {
+        let curve = curve(Interpolation::Linear, &[(5.0, 10.0)]);
+
+        assert_eq!(curve.duration(), secs(5.0));
+        assert_eq!(due(&curve, 25.0), 2.5);
+        assert_eq!(curve.time_at_count(50.0), None);
+        assert_eq!(curve.expected_requests(), 50);
+    }
+
+    #[test]
+    fn zero_rate_stretches_send_nothing() {
+        let curve = curve(
+            Interpolation::Linear,
+            &[(0.0, 0.0), (2.0, 0.0), (3.0, 50.0)],
+        );
+
+        assert_eq!(curve.expected_requests(), 25);
+        // Nothing is due before the rate picks up at 2s; half the requests by 2.707s
+        assert_eq!(curve.time_at_count(0.0), Some(secs(2.0)));
+        let half = curve.time_at_count(12.5).unwrap().as_secs_f64();
+        assert!((half - (2.0 + 0.5f64.sqrt())).abs() < 1e-9);
+        assert_eq!(curve.time_at_count(25.0), None);
+    }
+}
@@ -0,0 +1,558 @@
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Text};
+use rand::distr::Alphanumeric;
+use rand::Rng;
+use rand_chacha::ChaCha8Rng;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::error::DatasetError;
+use crate::pool::DbPool;
+use crate::seed::{RngStream, RunSeed};
+use crate::shutdown::CancellationToken;
+
+// Records every committed batch, so an interrupted seed can pick up where it stopped
+const PROGRESS_TABLE: &str = "supafast_seed_progress";
+
+const PROGRESS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// How `supafast seed` fills a table.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatasetSettings {
+    // Optionally schema-qualified, e.g. `bench.orders`
+    pub table: String,
+    // Create the table from `columns` if it does not exist yet
+    pub create_table: bool,
+    pub columns: Vec<ColumnSpec>,
+    // Stop once the table holds this many seeded rows...
+    pub rows: u64,
+    // ...or, when set, once it takes up this many bytes on disk (indexes included)
+    pub target_bytes: Option<u64>,
+    // Rows per INSERT; each batch commits on its own
+    pub batch_rows: u64,
+    // Parallel loader connections; 0 uses one per CPU
+    pub threads: usize,
+}
+
+impl Default for DatasetSettings {
+    fn default() -> Self {
+        Self {
+            table: "your_table".to_string(),
+            create_table: true,
+            columns: vec![
+                ColumnSpec {
+                    name: "id".to_string(),
+                    sql_type: "text".to_string(),
+                    generator: ColumnGenerator::Sequence {
+                        start: 0,
+                        prefix: Some("seed-".to_string()),
+                    },
+                },
+                ColumnSpec {
+                    name: "created_at".to_string(),
+                    sql_type: "timestamptz".to_string(),
+                    generator: ColumnGenerator::Now,
+                },
+            ],
+            rows: 1_000_000,
+            target_bytes: None,
+            batch_rows: 1000,
+            threads: 0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ColumnSpec {
+    pub name: String,
+    // Used only when creating the table
+    #[serde(rename = "type")]
+    pub sql_type: String,
+    pub generator: ColumnGenerator,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum ColumnGenerator {
+    // Row number plus `start`; text when a prefix is given
+    Sequence {
+        #[serde(default)]
+        start: i64,
+        prefix: Option<String>,
+    },
+    Int {
+        min: i64,
+        max: i64,
+    },
+    Float {
+        min: f64,
+        max: f64,
+    },
+    // Random alphanumeric text
+    Text {
+        min_len: usize,
+        max_len: usize,
+    },
+    // One of the given values, uniformly
+    Choice {
+        values: Vec<String>,
+    },
+    // Random point in the last `within_days` days
+    Timestamp {
+        within_days: u64,
+    },
+    Uuid,
+    Now,
+}
+
+impl ColumnGenerator {
+    // Appends the SQL literal for row `row`; random values come from the batch's stream
+    fn write_value(&self, row: u64, rng: &mut ChaCha8Rng, out: &mut String) {
+        match self {
+            ColumnGenerator::Sequence { start, prefix } => {
+                let value = *start + row as i64;
+                match prefix {
+                    Some(prefix) => write_text(&format!("{}{}", prefix, value), out),
+                    None => write!(out, "{}", value).unwrap(),
+                }
+            }
+            ColumnGenerator::Int { min, max } => {
+                write!(out, "{}", rng.random_range(*min..=(*max).max(*min))).unwrap()
+            }
+            ColumnGenerator::Float { min, max } => {
+                let value = if max > min {
+                    rng.random_range(*min..*max)
+                } else {
+                    *min
+                };
+                write!(out, "{}", value).unwrap()
+            }
+            ColumnGenerator::Text { min_len, max_len } => {
+                let len = rng.random_range(*min_len..=(*max_len).max(*min_len));
+                let text: String = (0..len).map(|_| rng.sample(Alphanumeric) as char).collect();
+                write_text(&text, out);
+            }
+            ColumnGenerator::Choice { values } => match values.len() {
+                0 => out.push_str("NULL"),
+                n => write_text(&values[rng.random_range(0..n)], out),
+            },
+            ColumnGenerator::Timestamp { within_days } => {
+                let secs = rng.random_range(0..(within_days * 86_400).max(1));
+                write!(out, "NOW() - INTERVAL '{} seconds'", secs).unwrap()
+            }
+            ColumnGenerator::Uuid => {
+                let mut bytes: [u8; 16] = rng.random();
+                bytes[6] = (bytes[6] & 0x0f) | 0x40;
+                bytes[8] = (bytes[8] & 0x3f) | 0x80;
+                out.push('\'');
+                for (i, byte) in bytes.iter().enumerate() {
+                    if matches!(i, 4 | 6 | 8 | 10) {
+                        out.push('-');
+                    }
+                    write!(out, "{:02x}", byte).unwrap();
+                }
+                out.push('\'');
+            }
+            ColumnGenerator::Now => out.push_str("NOW()"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SeedReport {
+    pub table: String,
+    // Rows found from an earlier, interrupted seed
+    pub resumed_rows: u64,
+    pub inserted_rows: u64,
+    pub elapsed: Duration,
+    pub table_bytes: u64,
+    // Stopped by Ctrl-C/SIGTERM; run again to resume
+    pub partial: bool,
+}
+
+#[derive(QueryableByName)]
+struct SeededBatch {
+    #[diesel(sql_type = BigInt)]
+    first_row: i64,
+    #[diesel(sql_type = Integer)]
+    rows: i32,
+}
+
+#[derive(QueryableByName)]
+struct RowCount {
+    #[diesel(sql_type = BigInt)]
+    rows: i64,
+}
+
+#[derive(QueryableByName)]
+struct TableSize {
+    #[diesel(sql_type = BigInt)]
+    bytes: i64,
+}
+
+// Work shared by the loader threads
+struct SeedJob {
+    settings: DatasetSettings,
+    table: String,
+    insert_prefix: String,
+    seed: RunSeed,
+    completed: HashSet<u64>,
+    next_batch: AtomicU64,
+    seeded_rows: AtomicU64,
+    table_bytes: AtomicU64,
+    stop: AtomicBool,
+    cancel: CancellationToken,
+}
+
+impl SeedJob {
+    // Rows in batch `batch`, or None once the row target has been handed out
+    fn batch_rows(&self, batch: u64) -> Option<u64> {
+        let first_row = batch * self.settings.batch_rows;
+        if self.settings.target_bytes.is_some() {
+            return Some(self.settings.batch_rows);
+        }
+        (first_row < self.settings.rows)
+            .then(|| self.settings.batch_rows.min(self.settings.rows - first_row))
+    }
+
+    fn target_reached(&self) -> bool {
+        match self.settings.target_bytes {
+            Some(bytes) => self.table_bytes.load(Ordering::Relaxed) >= bytes,
+            None => self.seeded_rows.load(Ordering::Relaxed) >= self.settings.rows,
+        }
+    }
+
+    fn insert_sql(&self, first_row: u64, rows: u64, rng: &mut ChaCha8Rng) -> String {
+        let mut sql = String::with_capacity(self.insert_prefix.len() + rows as usize * 64);
+        sql.push_str(&self.insert_prefix);
+
+        for row in first_row..first_row + rows {
+            sql.push_str(if row == first_row { "(" } else { ", (" });
+            for (i, column) in self.settings.columns.iter().enumerate() {
+                if i > 0 {
+                    sql.push_str(", ");
+                }
+                column.generator.write_value(row, rng, &mut sql);
+            }
+            sql.push(')');
+        }
+
+        sql
+    }
+}
+
+/// Bulk-loads `settings.table` until it reaches the row or size target, resuming from
+/// the batches an earlier, interrupted seed committed.
+pub fn run_seed(
+    pool: &DbPool,
+    settings: &DatasetSettings,
+    seed: RunSeed,
+    threads: usize,
+    cancel: CancellationToken,
+) -> Result<SeedReport, DatasetError> {
+    if settings.columns.is_empty() {
+        return Err(DatasetError::NoColumns);
+    }
+    let table = quote_qualified(&settings.table)
+        .ok_or_else(|| DatasetError::InvalidTable(settings.table.clone()))?;
+    let batch_rows = settings.batch_rows.max(1);
+
+    let mut conn = pool.get()?;
+    prepare_tables(&mut conn, &table, settings)?;
+
+    let mut completed: Vec<SeededBatch> = diesel::sql_query(format!(
+        "SELECT first_row, rows FROM {} WHERE table_name = $1",
+        PROGRESS_TABLE
+    ))
+    .bind::<Text, _>(&settings.table)
+    .load(&mut conn)?;
+
+    // Progress outlives the rows it describes when the table is truncated or recreated;
+    // resuming from it then would skip batches that are no longer there
+    let recorded_rows: u64 = completed.iter().map(|batch| batch.rows as u64).sum();
+    if recorded_rows > 0 {
+        let table_rows = row_count(&mut conn, &table)?;
+        if table_rows < recorded_rows {
+            println!(
+                "⚠️  {} holds {} rows but {} were recorded as seeded; starting over",
+                table, table_rows, recorded_rows
+            );
+            diesel::sql_query(format!(
+                "DELETE FROM {} WHERE table_name = $1",
+                PROGRESS_TABLE
+            ))
+            .bind::<Text, _>(&settings.table)
+            .execute(&mut conn)?;
+            completed.clear();
+        }
+    }
+
+    if let Some(batch) = completed
+        .iter()
+        .find(|batch| !(batch.first_row as u64).is_multiple_of(batch_rows))
+    {
+        return Err(DatasetError::BatchSizeChanged {
+            first_row: batch.first_row as u64,
+            batch_rows,
+        });
+    }
+    let resumed_rows: u64 = completed.iter().map(|batch| batch.rows as u64).sum();
+
+    let columns: Vec<String> = settings
+        .columns
+        .iter()
+        .map(|column| quote_ident(&column.name))
+        .collect();
+
+    let job = Arc::new(SeedJob {
+        settings: DatasetSettings {
+            batch_rows,
+            ..settings.clone()
+        },
+        insert_prefix: format!("INSERT INTO {} ({}) VALUES ", table, columns.join(", ")),
+        table: table.clone(),
+        seed,
+        completed: completed
+            .iter()
+            .map(|batch| batch.first_row as u64 / batch_rows)
+            .collect(),
+        next_batch: AtomicU64::new(0),
+        seeded_rows: AtomicU64::new(resumed_rows),
+        table_bytes: AtomicU64::new(table_size(&mut conn, &table)?),
+        stop: AtomicBool::new(false),
+        cancel,
+    });
+    drop(conn);
+
+    if resumed_rows > 0 {
+        println!(
+            "♻️  Resuming: {} rows already seeded in {} batches",
+            resumed_rows,
+            job.completed.len()
+        );
+    }
+
+    let start = Instant::now();
+    let handles: Vec<_> = (0..threads.max(1))
+        .map(|loader_id| {
+            let job = Arc::clone(&job);
+            let pool = pool.clone();
+
+            thread::Builder::new()
+                .name(format!("seed-loader-{}", loader_id))
+                .spawn(move || {
+                    let result = load_batches(&pool, &job);
+                    if result.is_err() {
+                        job.stop.store(true, Ordering::Relaxed);
+                    }
+                    result
+                })
+                .expect("Failed to spawn seed loader thread")
+        })
+        .collect();
+
+    let mut last_progress = Instant::now();
+    while !handles.iter().all(|handle| handle.is_finished()) {
+        thread::sleep(Duration::from_millis(50));
+        if last_progress.elapsed() >= PROGRESS_INTERVAL {
+            print_progress(&job, resumed_rows, start.elapsed());
+            last_progress = Instant::now();
+        }
+    }
+
+    for handle in handles {
+        handle.join().expect("Seed loader panicked")?;
+    }
+
+    let seeded_rows = job.seeded_rows.load(Ordering::Relaxed);
+    let mut conn = pool.get()?;
+
+    Ok(SeedReport {
+        table,
+        resumed_rows,
+        inserted_rows: seeded_rows - resumed_rows,
+        elapsed: start.elapsed(),
+        table_bytes: table_size(&mut conn, &job.table)?,
+        partial: !job.target_reached(),
+    })
+}
+
+fn load_batches(pool: &DbPool, job: &SeedJob) -> Result<(), DatasetError> {
+    let mut conn = pool.get()?;
+    let insert_progress = format!(
+        "INSERT INTO {} (table_name, first_row, rows) VALUES ($1, $2, $3)",
+        PROGRESS_TABLE
+    );
+
+    while !job.stop.load(Ordering::Relaxed) && !job.cancel.is_cancelled() {
+        if job.settings.target_bytes.is_some() && job.target_reached() {
+            break;
+        }
+
+        let batch = job.next_batch.fetch_add(1, Ordering::Relaxed);
+        let Some(rows) = job.batch_rows(batch) else {
+            break;
+        };
+        if job.completed.contains(&batch) {
+            continue;
+        }
+
+        let first_row = batch * job.settings.batch_rows;
+        let mut rng = job.seed.rng(RngStream::Dataset, batch);
+        let insert = job.insert_sql(first_row, rows, &mut rng);
+
+        conn.transaction(|conn| {
+            diesel::sql_query(&insert).execute(conn)?;
+            diesel::sql_query(&insert_progress)
+                .bind::<Text, _>(&job.settings.table)
+                .bind::<BigInt, _>(first_row as i64)
+                .bind::<Integer, _>(rows as i32)
+                .execute(conn)
+        })?;
+        job.seeded_rows.fetch_add(rows, Ordering::Relaxed);
+
+        if job.settings.target_bytes.is_some() {
+            let bytes = table_size(&mut conn, &job.table)?;
+            job.table_bytes.fetch_max(bytes, Ordering::Relaxed);
+        }
+    }
+
+    Ok(())
+}
+
+fn prepare_tables(
+    conn: &mut PgConnection,
+    table: &str,
+    settings: &DatasetSettings,
+) -> Result<(), DatasetError> {
+    if settings.create_table {
+        let mut columns = Vec::with_capacity(settings.columns.len());
+        for column in &settings.columns {
+            if !is_valid_type(&column.sql_type) {
+                return Err(DatasetError::InvalidType(column.sql_type.clone()));
+            }
+            columns.push(format!("{} {}", quote_ident(&column.name), column.sql_type));
+        }
+
+        diesel::sql_query(format!(
+            "CREATE TABLE IF NOT EXISTS {} ({})",
+            table,
+            columns.join(", ")
+        ))
+        .execute(conn)?;
+    }
+
+    diesel::sql_query(format!(
+        "CREATE TABLE IF NOT EXISTS {} (\
+         table_name text NOT NULL, \
+         first_row bigint NOT NULL, \
+         rows integer NOT NULL, \
+         PRIMARY KEY (table_name, first_row))",
+        PROGRESS_TABLE
+    ))
+    .execute(conn)?;
+
+    Ok(())
+}
+
+fn row_count(conn: &mut PgConnection, table: &str) -> Result<u64, DatasetError> {
+    let count: RowCount =
+        diesel::sql_query(format!("SELECT count(*) AS rows FROM {}", table)).get_result(conn)?;
+
+    Ok(count.rows as u64)
+}
+
+fn table_size(conn: &mut PgConnection, table: &str) -> Result<u64, DatasetError> {
+    let size: TableSize = diesel::sql_query("SELECT pg_total_relation_size($1::regclass) AS bytes")
+        .bind::<Text, _>(table)
+        .get_result(conn)?;
+
+    Ok(size.bytes as u64)
+}
+
+fn print_progress(job: &SeedJob, resumed_rows: u64, elapsed: Duration) {
+    let seeded = job.seeded_rows.load(Ordering::Relaxed);
+    let rate = (seeded - resumed_rows) as f64 / elapsed.as_secs_f64().max(f64::EPSILON);
+
+    match job.settings.target_bytes {
+        Some(target) => {
+            let bytes = job.table_bytes.load(Ordering::Relaxed);
+            println!(
+                "🌱 {} rows, {:.1} MB of {:.1} MB ({:.1}%), {:.0} rows/s",
+                seeded,
+                bytes as f64 / 1_048_576.0,
+                target as f64 / 1_048_576.0,
+                bytes as f64 / target.max(1) as f64 * 100.0,
+                rate
+            );
+        }
+        None => println!(
+            "🌱 {} of {} rows ({:.1}%), {:.0} rows/s",
+            seeded,
+            job.settings.rows,
+            seeded as f64 / job.settings.rows.max(1) as f64 * 100.0,
+            rate
+        ),
+    }
+}
+
+pub fn print_seed_report(report: &SeedReport) {
+    println!("\n🌱 Seeded {}:", report.table);
+    println!("  Resumed Rows:        {:>10}", report.resumed_rows);
+    println!("  Inserted Rows:       {:>10}", report.inserted_rows);
+    println!(
+        "  Rows/sec:            {:>10.0}",
+        report.inserted_rows as f64 / report.elapsed.as_secs_f64().max(f64::EPSILON)
+    );
+    println!(
+        "  Table Size:          {:>10.1} MB",
+        report.table_bytes as f64 / 1_048_576.0
+    );
+    println!(
+        "  Duration:            {:>10.3} s",
+        report.elapsed.as_secs_f64()
+    );
+    if report.partial {
+        println!("  ⚠️  Target not reached; run `supafast seed` again to resume");
+    }
+}
+
+fn write_text(value: &str, out: &mut String) {
+    out.push('\'');
+    out.push_str(&value.replace('\'', "''"));
+    out.push('\'');
+}
+
+fn quote_ident(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+// `schema.table` becomes `"schema"."table"`
+pub(crate) fn quote_qualified(name: &str) -> Option<String> {
+    if name.is_empty() || name.split('.').any(str::is_empty) {
+        return None;
+    }
+
+    Some(
+        name.split('.')
+            .map(quote_ident)
+            .collect::<Vec<_>>()
+            .join("."),
+    )
+}
+
+// Column types go into CREATE TABLE verbatim, so only allow what type names need,
+// e.g. `numeric(10, 2)` or `text[]`
+fn is_valid_type(sql_type: &str) -> bool {
+    !sql_type.trim().is_empty()
+        && sql_type
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || " _(),[]".contains(c))
+}
@@ -1,10 +1,14 @@
 use thiserror::Error;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Error, Debug)]
 pub enum WorkerError {
     #[error("Database connection error")]
     ConnectionError(#[from] diesel::r2d2::PoolError),
 
+    #[error("Database connect error")]
+    EstablishError(#[from] diesel::ConnectionError),
+
     #[error("Database query error")]
     DatabaseError(#[from] diesel::result::Error),
 
@@ -17,6 +21,84 @@ pub enum WorkerError {
 
 #[derive(Error, Debug)]
 pub enum PoolError {
-    #[error("Failed to create connection pool")]
+    #[error("Failed to create connection pool: {0}")]
     CreationError(#[from] diesel::r2d2::PoolError),
+
+    #[error("Invalid session setting name: {0:?}")]
+    InvalidSessionSetting(String),
+}
+
+#[derive(Error, Debug)]
+pub enum EngineError {
+    #[error("Failed to start async runtime: {0}")]
+    Runtime(#[from] std::io::Error),
+
+    #[error("Async engine database error: {0}")]
+    Database(#[from] tokio_postgres::Error),
+}
+
+#[derive(Error, Debug)]
+pub enum TraceError {
+    #[error("Failed to read trace file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Invalid trace line {line}: {reason}")]
+    Parse { line: usize, reason: String },
+
+    #[error("Trace file has no events")]
+    Empty,
+}
+
+#[derive(Error, Debug)]
+pub enum DatasetError {
+    #[error("Seed connection error: {0}")]
+    Pool(#[from] diesel::r2d2::PoolError),
+
+    #[error("Seed query error: {0}")]
+    Database(#[from] diesel::result::Error),
+
+    #[error("Invalid table name: {0:?}")]
+    InvalidTable(String),
+
+    #[error("Invalid column type: {0:?}")]
+    InvalidType(String),
+
+    #[error("Dataset has no columns")]
+    NoColumns,
+
+    #[error("Seeded batch at row {first_row} does not match batch_rows = {batch_rows}; resume with the batch size it was started with")]
+    BatchSizeChanged { first_row: u64, batch_rows: u64 },
+}
+
+#[derive(Error, Debug)]
+pub enum SchemaError {
+    #[error("Schema connection error: {0}")]
+    Pool(#[from] diesel::r2d2::PoolError),
+
+    #[error("Schema connect error: {0}")]
+    Connect(#[from] diesel::ConnectionError),
+
+    #[error("Schema query error: {0}")]
+    Database(#[from] diesel::result::Error),
+
+    #[error("Schema statement failed: {statement:?}: {source}")]
+    Statement {
+        statement: String,
+        source: diesel::result::Error,
+    },
+
+    #[error("Invalid table name: {0:?}")]
+    InvalidTable(String),
+}
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file: {0}")]
+    Io(#[from] std::io::Error),
+
+    #[error("Failed to parse config file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    #[error("Invalid config: {0}")]
+    Invalid(&'static str),
 }
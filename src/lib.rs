@@ -0,0 +1,25 @@
+pub mod arrival;
+pub mod async_engine;
+pub mod benchmark;
+pub mod closed_loop;
+pub mod config;
+pub mod curve;
+pub mod dataset;
+pub mod error;
+pub mod load_gen;
+pub mod metrics;
+pub mod models;
+pub mod pacer;
+pub mod pool;
+pub mod pool_metrics;
+pub mod replay;
+pub mod responses;
+pub mod runner;
+pub mod saturation;
+pub mod schema;
+pub mod seed;
+pub mod settings;
+pub mod shutdown;
+pub mod spike;
+pub mod worker;
+pub mod workload;
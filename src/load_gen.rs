@@ -1,8 +1,18 @@
-use crossbeam_channel::Sender;
+use crossbeam_channel::{SendError, SendTimeoutError, Sender, TrySendError};
+use serde::Deserialize;
+use std::ops::Range;
+use std::sync::Arc;
 use std::thread;
 use std::time::{Duration, Instant};
 
-use crate::models::{WorkRequest, WorkResponse};
+use crate::arrival::{ArrivalSampler, ArrivalSettings};
+use crate::curve::RateCurve;
+use crate::models::{WorkEnvelope, WorkRequest};
+use crate::pacer::{Pacer, PacingReport};
+use crate::replay::ReplayTrace;
+use crate::responses::ResponseTracker;
+use crate::seed::{RngStream, RunSeed};
+use crate::shutdown::CancellationToken;
 
 #[derive(Debug, Clone)]
 pub enum LoadPattern {
@@ -23,37 +33,177 @@ pub enum LoadPattern {
         amplitude: u64,
         period_secs: u64,
     },
+    // Staircase: `start_rps`, raised by `step_rps` every `step_secs`
+    Step {
+        start_rps: u64,
+        step_rps: u64,
+        step_secs: u64,
+    },
+    // Steady `base_rps`, surging to `multiplier` times that for `spike_secs` starting
+    // at `spike_at_secs`
+    Spike {
+        base_rps: u64,
+        multiplier: u64,
+        spike_at_secs: u64,
+        spike_secs: u64,
+    },
+    // Rate read off a data-defined curve until the curve ends
+    Curve {
+        curve: Arc<RateCurve>,
+    },
+    // Re-issues recorded requests at their original relative times, scaled by `speed`
+    Replay {
+        trace: Arc<ReplayTrace>,
+        speed: f64,
+    },
+}
+
+impl LoadPattern {
+    // Rate at the very start, used to stagger parallel generators
+    fn initial_rps(&self) -> f64 {
+        match *self {
+            LoadPattern::Constant { rps } | LoadPattern::Burst { rps, .. } => rps as f64,
+            LoadPattern::Ramp { start_rps, .. } | LoadPattern::Step { start_rps, .. } => {
+                start_rps as f64
+            }
+            LoadPattern::Sine { base_rps, .. } | LoadPattern::Spike { base_rps, .. } => {
+                base_rps as f64
+            }
+            // Curves and replays stagger their lanes themselves
+            LoadPattern::Curve { .. } | LoadPattern::Replay { .. } => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverloadPolicy {
+    // Wait for room on the queue, holding up the pattern
+    Block,
+    // Drop the request and count it as shed
+    Shed,
+    // Wait up to `send_timeout_ms`, then shed
+    Timeout,
+}
+
+/// What the generator does when the work queue is full.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackpressureSettings {
+    pub policy: OverloadPolicy,
+    pub send_timeout_ms: u64,
+}
+
+impl Default for BackpressureSettings {
+    fn default() -> Self {
+        Self {
+            policy: OverloadPolicy::Block,
+            send_timeout_ms: 10,
+        }
+    }
+}
+
+/// How a pattern is turned into individual sends.
+#[derive(Debug, Clone, Copy)]
+pub struct GeneratorOptions {
+    // Parallel generator threads sharing the pattern's rate
+    pub generators: usize,
+    pub arrivals: ArrivalSettings,
+    pub backpressure: BackpressureSettings,
+    pub seed: RunSeed,
+}
+
+// One generator's share of a pattern split across `count` threads
+#[derive(Debug, Clone, Copy)]
+struct Lane {
+    index: u64,
+    count: u64,
+    // Shared schedule start; None starts when generation does
+    epoch: Option<Instant>,
 }
 
 pub struct LoadGenerator {
     pattern: LoadPattern,
     total_requests: u64,
+    arrivals: ArrivalSampler,
+    pacer: Pacer,
+    lane: Lane,
+    backpressure: BackpressureSettings,
+    dispatched: u64,
+    shed: u64,
+    blocked: u64,
+    blocked_time: Duration,
+    cancel: CancellationToken,
 }
 
 impl LoadGenerator {
-    pub fn new(pattern: LoadPattern, total_requests: u64) -> Self {
+    pub fn new(
+        pattern: LoadPattern,
+        total_requests: u64,
+        arrivals: ArrivalSampler,
+        cancel: CancellationToken,
+    ) -> Self {
         Self {
             pattern,
             total_requests,
+            arrivals,
+            pacer: Pacer::start().with_cancel(cancel.clone()),
+            lane: Lane {
+                index: 0,
+                count: 1,
+                epoch: None,
+            },
+            backpressure: BackpressureSettings::default(),
+            dispatched: 0,
+            shed: 0,
+            blocked: 0,
+            blocked_time: Duration::ZERO,
+            cancel,
         }
     }
 
+    pub fn with_backpressure(mut self, backpressure: BackpressureSettings) -> Self {
+        self.backpressure = backpressure;
+        self
+    }
+
+    // Sends `1/count` of the pattern's rate, phase-shifted so lanes interleave; replays
+    // take every `count`-th event instead
+    fn with_lane(mut self, lane: Lane) -> Self {
+        self.lane = lane;
+        self
+    }
+
     pub fn generate<F>(
-        &self,
-        work_sender: Sender<(WorkRequest, Sender<WorkResponse>)>,
+        &mut self,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
         request_factory: F,
-    ) -> Duration
+    ) -> PacingReport
     where
         F: FnMut(u64) -> WorkRequest,
     {
-        let start = Instant::now();
+        self.pacer = Pacer::start_at(self.lane.epoch.unwrap_or_else(Instant::now))
+            .with_cancel(self.cancel.clone());
+        self.dispatched = 0;
+        self.shed = 0;
+        self.blocked = 0;
+        self.blocked_time = Duration::ZERO;
+
+        // Lane k starts k/count of an interval in, so uniform sends do not clump
+        let initial_rps = self.pattern.initial_rps();
+        if self.lane.index > 0 && initial_rps > 0.0 {
+            self.pacer.wait_until(Duration::from_secs_f64(
+                self.lane.index as f64 / initial_rps,
+            ));
+        }
 
         match self.pattern {
             LoadPattern::Constant { rps } => {
-                self.generate_constant(rps, work_sender, request_factory)
+                self.generate_constant(rps, work_sender, responses, request_factory)
             }
             LoadPattern::Burst { rps, duration_secs } => {
-                self.generate_burst(rps, duration_secs, work_sender, request_factory)
+                self.generate_burst(rps, duration_secs, work_sender, responses, request_factory)
             }
             LoadPattern::Ramp {
                 start_rps,
@@ -64,6 +214,7 @@ impl LoadGenerator {
                 end_rps,
                 duration_secs,
                 work_sender,
+                responses,
                 request_factory,
             ),
             LoadPattern::Sine {
@@ -75,68 +226,172 @@ impl LoadGenerator {
                 amplitude,
                 period_secs,
                 work_sender,
+                responses,
+                request_factory,
+            ),
+            LoadPattern::Step {
+                start_rps,
+                step_rps,
+                step_secs,
+            } => self.generate_step(
+                start_rps,
+                step_rps,
+                step_secs,
+                work_sender,
+                responses,
+                request_factory,
+            ),
+            LoadPattern::Spike {
+                base_rps,
+                multiplier,
+                spike_at_secs,
+                spike_secs,
+            } => self.generate_spike(
+                base_rps,
+                multiplier,
+                Duration::from_secs(spike_at_secs)..Duration::from_secs(spike_at_secs + spike_secs),
+                work_sender,
+                responses,
                 request_factory,
             ),
+            LoadPattern::Curve { ref curve } => {
+                let curve = Arc::clone(curve);
+                self.generate_curve(&curve, work_sender, responses, request_factory)
+            }
+            LoadPattern::Replay { ref trace, speed } => {
+                let trace = Arc::clone(trace);
+                self.generate_replay(&trace, speed, work_sender, responses, request_factory)
+            }
+        }
+
+        PacingReport {
+            blocked: self.blocked,
+            blocked_time: self.blocked_time,
+            ..self.pacer.finish(self.dispatched, self.shed)
         }
+    }
 
-        start.elapsed()
+    #[inline]
+    fn pace(&mut self, rps: f64) {
+        let gap = self.arrivals.next_gap(rps / self.lane.count as f64);
+        self.pacer.pace(gap);
+    }
+
+    // Registers the request with the tracker and queues it, applying the overload policy
+    // when the queue is full; false once the queue is closed
+    #[inline]
+    fn dispatch(
+        &mut self,
+        work_sender: &Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
+        request: WorkRequest,
+    ) -> bool {
+        let envelope = match work_sender.try_send(responses.envelope(request)) {
+            Ok(()) => {
+                self.dispatched += 1;
+                return true;
+            }
+            Err(TrySendError::Full(envelope)) => envelope,
+            Err(TrySendError::Disconnected(envelope)) => {
+                responses.cancel(envelope.seq);
+                return false;
+            }
+        };
+
+        if self.backpressure.policy == OverloadPolicy::Shed {
+            responses.cancel(envelope.seq);
+            self.shed += 1;
+            return true;
+        }
+
+        self.blocked += 1;
+        let start = Instant::now();
+        let result = match self.backpressure.policy {
+            OverloadPolicy::Timeout => work_sender.send_timeout(
+                envelope,
+                Duration::from_millis(self.backpressure.send_timeout_ms),
+            ),
+            _ => work_sender
+                .send(envelope)
+                .map_err(|SendError(envelope)| SendTimeoutError::Disconnected(envelope)),
+        };
+        self.blocked_time += start.elapsed();
+
+        match result {
+            Ok(()) => {
+                self.dispatched += 1;
+                true
+            }
+            Err(SendTimeoutError::Timeout(envelope)) => {
+                responses.cancel(envelope.seq);
+                self.shed += 1;
+                true
+            }
+            Err(SendTimeoutError::Disconnected(envelope)) => {
+                responses.cancel(envelope.seq);
+                false
+            }
+        }
     }
 
     fn generate_constant<F>(
-        &self,
+        &mut self,
         rps: u64,
-        work_sender: Sender<(WorkRequest, Sender<WorkResponse>)>,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
         mut request_factory: F,
     ) where
         F: FnMut(u64) -> WorkRequest,
     {
-        let interval = Duration::from_secs_f64(1.0 / rps as f64);
-
         for i in 0..self.total_requests {
-            let request = request_factory(i);
-            let (response_tx, _response_rx) = crossbeam_channel::bounded(1);
+            if self.cancel.is_cancelled() {
+                break;
+            }
 
-            if work_sender.send((request, response_tx)).is_err() {
+            let request = request_factory(i);
+            if !self.dispatch(&work_sender, responses, request) {
                 break;
             }
 
-            thread::sleep(interval);
+            self.pace(rps as f64);
         }
     }
 
     fn generate_burst<F>(
-        &self,
+        &mut self,
         rps: u64,
         duration_secs: u64,
-        work_sender: Sender<(WorkRequest, Sender<WorkResponse>)>,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
         mut request_factory: F,
     ) where
         F: FnMut(u64) -> WorkRequest,
     {
-        let interval = Duration::from_secs_f64(1.0 / rps as f64);
         let start = Instant::now();
         let burst_duration = Duration::from_secs(duration_secs);
         let mut sent = 0;
 
-        while sent < self.total_requests && start.elapsed() < burst_duration {
+        while sent < self.total_requests
+            && start.elapsed() < burst_duration
+            && !self.cancel.is_cancelled()
+        {
             let request = request_factory(sent);
-            let (response_tx, _response_rx) = crossbeam_channel::bounded(1);
-
-            if work_sender.send((request, response_tx)).is_err() {
+            if !self.dispatch(&work_sender, responses, request) {
                 break;
             }
 
             sent += 1;
-            thread::sleep(interval);
+            self.pace(rps as f64);
         }
     }
 
     fn generate_ramp<F>(
-        &self,
+        &mut self,
         start_rps: u64,
         end_rps: u64,
         duration_secs: u64,
-        work_sender: Sender<(WorkRequest, Sender<WorkResponse>)>,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
         mut request_factory: F,
     ) where
         F: FnMut(u64) -> WorkRequest,
@@ -145,22 +400,19 @@ impl LoadGenerator {
         let total_duration = Duration::from_secs(duration_secs);
         let mut sent = 0;
 
-        while sent < self.total_requests {
+        while sent < self.total_requests && !self.cancel.is_cancelled() {
             let elapsed = start.elapsed().as_secs_f64();
             let progress = (elapsed / total_duration.as_secs_f64()).min(1.0);
 
             let current_rps = start_rps as f64 + (end_rps as f64 - start_rps as f64) * progress;
-            let interval = Duration::from_secs_f64(1.0 / current_rps);
 
             let request = request_factory(sent);
-            let (response_tx, _response_rx) = crossbeam_channel::bounded(1);
-
-            if work_sender.send((request, response_tx)).is_err() {
+            if !self.dispatch(&work_sender, responses, request) {
                 break;
             }
 
             sent += 1;
-            thread::sleep(interval);
+            self.pace(current_rps);
 
             if start.elapsed() >= total_duration {
                 break;
@@ -169,11 +421,12 @@ impl LoadGenerator {
     }
 
     fn generate_sine<F>(
-        &self,
+        &mut self,
         base_rps: u64,
         amplitude: u64,
         period_secs: u64,
-        work_sender: Sender<(WorkRequest, Sender<WorkResponse>)>,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
         mut request_factory: F,
     ) where
         F: FnMut(u64) -> WorkRequest,
@@ -181,41 +434,319 @@ impl LoadGenerator {
         let start = Instant::now();
         let mut sent = 0;
 
-        while sent < self.total_requests {
+        while sent < self.total_requests && !self.cancel.is_cancelled() {
             let elapsed = start.elapsed().as_secs_f64();
             let phase = (elapsed / period_secs as f64) * 2.0 * std::f64::consts::PI;
             let current_rps = base_rps as f64 + amplitude as f64 * phase.sin();
             let current_rps = current_rps.max(1.0);
 
-            let interval = Duration::from_secs_f64(1.0 / current_rps);
+            let request = request_factory(sent);
+            if !self.dispatch(&work_sender, responses, request) {
+                break;
+            }
+
+            sent += 1;
+            self.pace(current_rps);
+        }
+    }
+
+    fn generate_step<F>(
+        &mut self,
+        start_rps: u64,
+        step_rps: u64,
+        step_secs: u64,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
+        mut request_factory: F,
+    ) where
+        F: FnMut(u64) -> WorkRequest,
+    {
+        let start = Instant::now();
+        let step_secs = step_secs.max(1);
+        let mut sent = 0;
+
+        while sent < self.total_requests && !self.cancel.is_cancelled() {
+            let step = start.elapsed().as_secs() / step_secs;
+            let current_rps = (start_rps + step_rps * step).max(1);
 
             let request = request_factory(sent);
-            let (response_tx, _response_rx) = crossbeam_channel::bounded(1);
+            if !self.dispatch(&work_sender, responses, request) {
+                break;
+            }
 
-            if work_sender.send((request, response_tx)).is_err() {
+            sent += 1;
+            self.pace(current_rps as f64);
+        }
+    }
+
+    fn generate_spike<F>(
+        &mut self,
+        base_rps: u64,
+        multiplier: u64,
+        spike: Range<Duration>,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
+        mut request_factory: F,
+    ) where
+        F: FnMut(u64) -> WorkRequest,
+    {
+        let start = Instant::now();
+        let mut sent = 0;
+
+        while sent < self.total_requests && !self.cancel.is_cancelled() {
+            let current_rps = if spike.contains(&start.elapsed()) {
+                base_rps * multiplier.max(1)
+            } else {
+                base_rps
+            };
+
+            let request = request_factory(sent);
+            if !self.dispatch(&work_sender, responses, request) {
                 break;
             }
 
             sent += 1;
-            thread::sleep(interval);
+            self.pace(current_rps.max(1) as f64);
+        }
+    }
+
+    fn generate_curve<F>(
+        &mut self,
+        curve: &RateCurve,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
+        mut request_factory: F,
+    ) where
+        F: FnMut(u64) -> WorkRequest,
+    {
+        // Sends are spaced in expected requests rather than seconds and mapped to times
+        // through the curve's running total, so zero-rate stretches and rate changes
+        // between two sends are followed exactly. Lane k starts k requests in. Arrival
+        // gaps are drawn in the same units, which is why `on_off` is rejected with curves.
+        let lane_rate = 1.0 / self.lane.count as f64;
+        let mut position = self.lane.index as f64;
+        let mut sent = 0;
+
+        while sent < self.total_requests && !self.cancel.is_cancelled() {
+            let Some(due) = curve.time_at_count(position) else {
+                break;
+            };
+            if !self.pacer.wait_until(due) {
+                break;
+            }
+
+            let request = request_factory(sent);
+            if !self.dispatch(&work_sender, responses, request) {
+                break;
+            }
+
+            sent += 1;
+            position += self.arrivals.next_gap(lane_rate).as_secs_f64();
+        }
+    }
+
+    fn generate_replay<F>(
+        &mut self,
+        trace: &ReplayTrace,
+        speed: f64,
+        work_sender: Sender<WorkEnvelope>,
+        responses: &ResponseTracker,
+        mut request_factory: F,
+    ) where
+        F: FnMut(u64) -> WorkRequest,
+    {
+        let speed = if speed > 0.0 { speed } else { 1.0 };
+
+        let events = trace
+            .events
+            .iter()
+            .enumerate()
+            .take(self.total_requests as usize);
+        let lane = events
+            .skip(self.lane.index as usize)
+            .step_by(self.lane.count as usize);
+
+        for (i, event) in lane {
+            if self.cancel.is_cancelled() {
+                break;
+            }
+
+            if !self.pacer.wait_until(event.offset.div_f64(speed)) {
+                break;
+            }
+
+            let mut request = request_factory(i as u64);
+            request.operation = event.operation;
+            request.params = event.params.clone();
+            if !self.dispatch(&work_sender, responses, request) {
+                break;
+            }
         }
     }
 }
 
+/// Generates the pattern on `options.generators` threads sharing one schedule, and
+/// returns their combined pacing once all of them are done.
 pub fn spawn_load_generator<F>(
     pattern: LoadPattern,
     total_requests: u64,
-    work_sender: Sender<(WorkRequest, Sender<WorkResponse>)>,
+    work_sender: Sender<WorkEnvelope>,
+    responses: ResponseTracker,
+    options: GeneratorOptions,
+    cancel: CancellationToken,
     request_factory: F,
-) -> thread::JoinHandle<Duration>
+) -> thread::JoinHandle<PacingReport>
 where
-    F: FnMut(u64) -> WorkRequest + Send + 'static,
+    F: Fn(u64) -> WorkRequest + Send + Sync + 'static,
 {
+    let count = options.generators.max(1) as u64;
+    let request_factory = Arc::new(request_factory);
+    let replay = matches!(pattern, LoadPattern::Replay { .. });
+
+    let base_quota = total_requests / count;
+    let remainder = total_requests % count;
+
+    let epoch = Instant::now();
+    let lanes: Vec<_> = (0..count)
+        .map(|index| {
+            let (quota, first_id) = if replay {
+                (total_requests, 0)
+            } else {
+                (
+                    base_quota + u64::from(index < remainder),
+                    index * base_quota + index.min(remainder),
+                )
+            };
+
+            let pattern = pattern.clone();
+            let work_sender = work_sender.clone();
+            let responses = responses.clone_handle();
+            let request_factory = Arc::clone(&request_factory);
+            let arrivals = ArrivalSampler::new(
+                options.arrivals,
+                options.seed.rng(RngStream::Generator, index),
+            );
+            let cancel = cancel.clone();
+
+            thread::Builder::new()
+                .name(format!("load-generator-{}", index))
+                .spawn(move || {
+                    let mut generator = LoadGenerator::new(pattern, quota, arrivals, cancel)
+                        .with_backpressure(options.backpressure)
+                        .with_lane(Lane {
+                            index,
+                            count,
+                            epoch: Some(epoch),
+                        });
+                    generator.generate(work_sender, &responses, |i| request_factory(first_id + i))
+                })
+                .expect("Failed to spawn load generator thread")
+        })
+        .collect();
+
     thread::Builder::new()
         .name("load-generator".to_string())
         .spawn(move || {
-            let generator = LoadGenerator::new(pattern, total_requests);
-            generator.generate(work_sender, request_factory)
+            let lanes: Vec<PacingReport> = lanes
+                .into_iter()
+                .map(|handle| handle.join().expect("Load generator panicked"))
+                .collect();
+            PacingReport::combine(lanes)
         })
         .expect("Failed to spawn load generator thread")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::responses::ResponseCollector;
+    use crossbeam_channel::bounded;
+
+    fn generator(policy: OverloadPolicy) -> LoadGenerator {
+        let arrivals = ArrivalSampler::new(
+            ArrivalSettings::default(),
+            RunSeed::new(1).rng(RngStream::Generator, 0),
+        );
+
+        LoadGenerator::new(
+            LoadPattern::Constant { rps: 100 },
+            10,
+            arrivals,
+            CancellationToken::new(),
+        )
+        .with_backpressure(BackpressureSettings {
+            policy,
+            send_timeout_ms: 10,
+        })
+    }
+
+    fn request(i: u64) -> WorkRequest {
+        WorkRequest::new(format!("req-{}", i))
+    }
+
+    // Dispatches three requests onto a queue with room for one that nobody reads
+    fn overload(generator: &mut LoadGenerator, collector: &ResponseCollector) -> u64 {
+        let tracker = collector.tracker();
+        let (tx, _rx) = bounded(1);
+
+        for i in 0..3 {
+            assert!(generator.dispatch(&tx, &tracker, request(i)));
+        }
+        tracker.outstanding()
+    }
+
+    #[test]
+    fn shed_drops_what_does_not_fit() {
+        let mut generator = generator(OverloadPolicy::Shed);
+        let collector = ResponseCollector::start(3);
+
+        let outstanding = overload(&mut generator, &collector);
+
+        assert_eq!((generator.dispatched, generator.shed), (1, 2));
+        assert_eq!(generator.blocked, 0);
+        assert_eq!(outstanding, 1);
+        assert_eq!(collector.finish().dispatched, 1);
+    }
+
+    #[test]
+    fn timeout_waits_then_sheds() {
+        let mut generator = generator(OverloadPolicy::Timeout);
+        let collector = ResponseCollector::start(3);
+
+        let outstanding = overload(&mut generator, &collector);
+
+        assert_eq!((generator.dispatched, generator.shed), (1, 2));
+        assert_eq!(generator.blocked, 2);
+        assert!(generator.blocked_time >= Duration::from_millis(20));
+        assert_eq!(outstanding, 1);
+        assert_eq!(collector.finish().dispatched, 1);
+    }
+
+    #[test]
+    fn block_waits_for_room_and_stops_on_a_closed_queue() {
+        let mut generator = generator(OverloadPolicy::Block);
+        let collector = ResponseCollector::start(3);
+        let tracker = collector.tracker();
+        let (tx, rx) = bounded(1);
+
+        assert!(generator.dispatch(&tx, &tracker, request(0)));
+        // Frees one slot a little later, then closes the queue
+        let reader = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(30));
+            drop(rx.recv());
+            rx
+        });
+        assert!(generator.dispatch(&tx, &tracker, request(1)));
+        drop(reader.join().unwrap());
+        assert!(!generator.dispatch(&tx, &tracker, request(2)));
+
+        assert_eq!((generator.dispatched, generator.shed), (2, 0));
+        assert_eq!(generator.blocked, 1);
+        assert!(generator.blocked_time >= Duration::from_millis(20));
+        // The request taken off the queue was never answered; the refused one is forgotten
+        assert_eq!(tracker.outstanding(), 2);
+        drop((tx, tracker));
+        let report = collector.finish();
+        assert_eq!((report.dispatched, report.lost), (2, 2));
+    }
+}
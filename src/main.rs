@@ -1,20 +1,24 @@
-mod benchmark;
-mod config;
-mod error;
-mod load_gen;
-mod metrics;
-mod models;
-mod pool;
-mod worker;
-
-use benchmark::{print_benchmark_report, BenchmarkCollector};
-use config::ConfigManager;
-use crossbeam_channel::bounded;
-use load_gen::{spawn_load_generator, LoadPattern};
-use metrics::MetricsCollector;
-use models::{WorkRequest, WorkResponse};
-use pool::create_pool;
-use worker::spawn_worker_pool_with_metrics;
+use std::sync::Arc;
+
+use supafast::benchmark::{print_benchmark_report, print_warmup_report};
+use supafast::closed_loop::{print_closed_loop_report, print_user_sweep, ClosedLoopReport};
+use supafast::config::ConfigManager;
+use supafast::curve::RateCurve;
+use supafast::dataset::{print_seed_report, run_seed};
+use supafast::load_gen::LoadPattern;
+use supafast::pacer::print_pacing_report;
+use supafast::pool::{create_pool, ConnectionSource, ConnectionStrategy};
+use supafast::pool_metrics::print_pool_report;
+use supafast::replay::ReplayTrace;
+use supafast::responses::print_response_report;
+use supafast::runner::{print_run_comparison, run_benchmark, EngineKind, LoadMode, RunOptions};
+use supafast::saturation::{print_saturation_report, run_saturation_search};
+use supafast::schema::{RunSchema, SchemaManager};
+use supafast::seed::RunSeed;
+use supafast::settings::Settings;
+use supafast::shutdown::{install_signal_handler, CancellationToken};
+use supafast::spike::print_spike_report;
+use supafast::workload::{QueryMode, Workload, WorkloadKind};
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
     let database_url = std::env::var("DATABASE_URL")
@@ -27,69 +31,260 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 Starting performance benchmark skeleton");
     println!("📊 Worker count: {}", worker_count);
 
-    let pool = create_pool(&database_url, worker_count)?;
+    let mut settings = Settings::load()?;
 
-    let queue_capacity = worker_count * 100;
-    let (tx, rx) =
-        bounded::<(WorkRequest, crossbeam_channel::Sender<WorkResponse>)>(queue_capacity);
+    // First Ctrl-C/SIGTERM stops the load and drains; a second one exits immediately
+    let cancel = CancellationToken::new();
+    install_signal_handler(cancel.clone())?;
 
-    // Create metrics collector
-    let metrics = MetricsCollector::new();
+    // Rerun with this seed in `[run]` to get the same request sequence
+    let seed = RunSeed::from_config(settings.run.seed);
+    println!("🎲 Run seed: {}", seed);
 
-    // Create benchmark collector
-    let benchmark = BenchmarkCollector::new();
+    // `supafast seed` bulk-loads the configured dataset instead of benchmarking
+    let seed_command = match std::env::args().nth(1) {
+        None => false,
+        Some(command) if command == "seed" => true,
+        Some(command) => {
+            return Err(format!("Unknown command: {:?} (expected `seed`)", command).into());
+        }
+    };
 
-    // Create shared config
-    let config = ConfigManager::new();
+    // Created before any pool so every connection, pooled or not, starts on its search_path.
+    // Held until main returns, however it returns; dropping it drops the schema. Seeding
+    // is never isolated, since its data would be dropped along with the schema.
+    let _run_schema = if settings.schema.isolate && !seed_command {
+        let schema = RunSchema::create(&database_url, settings.schema.keep_schema)?;
+        schema.apply(&mut settings.pool);
+        println!("🧪 Isolated schema: {}", schema.name());
+        Some(schema)
+    } else {
+        None
+    };
 
-    // Spawn workers with metrics and benchmarking
-    let handles = spawn_worker_pool_with_metrics(
-        worker_count,
-        pool,
-        rx,
-        metrics.clone_handle(),
-        Some(benchmark.clone_handle()),
-    );
+    if seed_command {
+        if settings.schema.isolate {
+            println!("ℹ️  Seeding ignores [schema] isolate and loads into the configured table");
+        }
 
-    println!("✅ Worker pool started with {} workers\n", worker_count);
+        let loaders = match settings.dataset.threads {
+            0 => worker_count,
+            threads => threads,
+        };
+        let pool = create_pool(&database_url, loaders, &settings.pool)?;
+        let report = run_seed(&pool, &settings.dataset, seed, loaders, cancel)?;
+        print_seed_report(&report);
+
+        return Ok(());
+    }
+
+    let pool = create_pool(&database_url, worker_count, &settings.pool)?;
+
+    // Create shared config
+    let config = ConfigManager::new();
 
     // Choose a load pattern - modify this for different tests
     let load_pattern = LoadPattern::Constant { rps: 100 };
     // let load_pattern = LoadPattern::Burst { rps: 500, duration_secs: 10 };
     // let load_pattern = LoadPattern::Ramp { start_rps: 10, end_rps: 200, duration_secs: 30 };
     // let load_pattern = LoadPattern::Sine { base_rps: 100, amplitude: 50, period_secs: 20 };
+    // let load_pattern = LoadPattern::Step { start_rps: 50, step_rps: 50, step_secs: 5 };
+    // let load_pattern = LoadPattern::Spike { base_rps: 100, multiplier: 5, spike_at_secs: 3, spike_secs: 2 };
 
     let total_requests = 1000;
 
-    println!("📈 Load pattern: {:?}", load_pattern);
+    // A configured rate curve replaces the pattern and sends what the curve adds up to
+    let (load_pattern, total_requests) = match RateCurve::new(&settings.curve) {
+        Some(curve) => {
+            println!("📉 Rate curve: {:?}", curve);
+
+            let expected = curve.expected_requests();
+            let pattern = LoadPattern::Curve {
+                curve: Arc::new(curve),
+            };
+            (pattern, expected)
+        }
+        None => (load_pattern, total_requests),
+    };
+
+    // A configured trace replaces the synthetic pattern and sends every recorded event
+    let (load_pattern, total_requests) = match settings.replay.trace {
+        Some(ref path) => {
+            let trace = ReplayTrace::from_file(path)?;
+            println!("📼 Replaying {:?} at {}x", trace, settings.replay.speed);
+
+            let events = trace.events.len() as u64;
+            let pattern = LoadPattern::Replay {
+                trace: Arc::new(trace),
+                speed: settings.replay.speed,
+            };
+            (pattern, events)
+        }
+        None => (load_pattern, total_requests),
+    };
+
+    // Closed-loop user counts, when configured, replace the open-loop pattern
+    let think_time = std::time::Duration::from_millis(settings.closed_loop.think_time_ms);
+    let modes: Vec<LoadMode> = if settings.closed_loop.users.is_empty() {
+        vec![LoadMode::Open(load_pattern)]
+    } else {
+        settings
+            .closed_loop
+            .users
+            .iter()
+            .map(|&users| LoadMode::Closed { users, think_time })
+            .collect()
+    };
+
     println!("📦 Total requests: {}\n", total_requests);
 
-    // Spawn load generator
-    let load_handle =
-        spawn_load_generator(load_pattern, total_requests, tx.clone(), |i| WorkRequest {
-            id: format!("req-{}", i),
-        });
-
-    // Wait for load generation to complete
-    let generation_time = load_handle.join().expect("Load generator panicked");
-    println!(
-        "⏱️  Load generation completed in {:.2}s\n",
-        generation_time.as_secs_f64()
-    );
-
-    // Give workers time to process remaining requests
-    std::thread::sleep(std::time::Duration::from_secs(2));
-
-    // Print metrics
-    let snapshot = metrics.get_snapshot();
-    println!("📊 Metrics Summary:");
-    println!("  Processed: {}", snapshot.total_processed);
-    println!("  Succeeded: {}", snapshot.total_succeeded);
-    println!("  Failed:    {}", snapshot.total_failed);
-
-    // Print benchmark report
-    let stats = benchmark.get_stats();
-    print_benchmark_report(&stats);
+    let mut options = RunOptions {
+        worker_count,
+        total_requests,
+        mode: modes[0].clone(),
+        workload: settings.workload.workloads()[0],
+        pool_sample_interval: std::time::Duration::from_millis(settings.pool.sample_interval_ms),
+        engine: settings.engine.clone(),
+        warmup: settings.warmup,
+        arrival: settings.arrival,
+        generators: settings.run.generators,
+        backpressure: settings.backpressure,
+        seed,
+        drain_timeout: std::time::Duration::from_secs(settings.run.drain_timeout_secs),
+        shutdown_grace: std::time::Duration::from_secs(settings.run.shutdown_grace_secs),
+        cancel: cancel.clone(),
+    };
+
+    let connections = ConnectionSource::new(
+        pool,
+        ConnectionStrategy::Cached,
+        &database_url,
+        &settings.pool,
+    )?;
+
+    let mut schema = SchemaManager::new(connections.pool.clone(), &settings.schema)?;
+    schema.setup()?;
+
+    // One run per connection strategy x workload kind x query mode (COPY ignores the
+    // mode), each through every load mode; more than one run gets a comparison table.
+    // The async engine has its own clients and only runs prepared single-row inserts.
+    let (strategies, workloads) = match settings.engine.kind {
+        EngineKind::Threaded => (
+            settings.worker.connection_strategies.clone(),
+            settings.workload.workloads(),
+        ),
+        EngineKind::Async => (
+            vec![ConnectionStrategy::Cached],
+            vec![Workload {
+                kind: WorkloadKind::Insert,
+                query_mode: QueryMode::Prepared,
+                ..options.workload
+            }],
+        ),
+    };
+    let setups: Vec<_> = strategies
+        .iter()
+        .flat_map(|&strategy| workloads.iter().map(move |&workload| (strategy, workload)))
+        .collect();
+
+    if settings.saturation.enabled {
+        for (strategy, workload) in setups {
+            if cancel.is_cancelled() {
+                break;
+            }
+
+            options.workload = workload;
+            schema.begin_phase()?;
+            let report = run_saturation_search(
+                &connections.with_strategy(strategy),
+                &options,
+                &settings.saturation,
+            )?;
+            print_saturation_report(&report, &settings.saturation);
+        }
+
+        schema.teardown()?;
+        return Ok(());
+    }
+
+    let mut results = Vec::with_capacity(setups.len() * modes.len());
+    for (strategy, workload) in setups {
+        let first_result = results.len();
+        options.workload = workload;
+        println!("🧱 Workload: {}", workload);
+
+        for mode in &modes {
+            if cancel.is_cancelled() {
+                break;
+            }
+
+            println!("📈 Load mode: {}", mode);
+            // Replayed traces keep their recorded timing
+            if let LoadMode::Open(ref pattern) = mode {
+                if !matches!(pattern, LoadPattern::Replay { .. }) {
+                    println!("🎲 Arrivals: {}", settings.arrival.process);
+                }
+            }
+            options.mode = mode.clone();
+
+            schema.begin_phase()?;
+            let result = run_benchmark(connections.with_strategy(strategy), &options)?;
+
+            // Print metrics
+            if result.partial {
+                println!("⚠️  PARTIAL RESULTS: run was interrupted before its load completed");
+            }
+            println!("📊 Metrics Summary ({}):", result.label());
+            println!("  Processed: {}", result.metrics.total_processed);
+            println!("  Succeeded: {}", result.metrics.total_succeeded);
+            println!("  Failed:    {}", result.metrics.total_failed);
+            println!("  Abandoned: {}", result.abandoned);
+            println!(
+                "  Written:   {} rows ({:.0} rows/s, {:.2} MB/s)",
+                result.metrics.rows_written,
+                result.rows_per_sec(),
+                result.bytes_per_sec() / 1_048_576.0
+            );
+            println!("  Seed:      {}", options.seed);
+
+            // Print benchmark report
+            if let Some(ref warmup) = result.warmup {
+                print_warmup_report(warmup);
+            }
+            print_benchmark_report(&result.stats);
+            if let Some(ref pacing) = result.pacing {
+                print_pacing_report(pacing);
+            }
+            if result.engine == EngineKind::Threaded {
+                print_pool_report(&result.pool_stats);
+            }
+            if let Some(ref report) = result.responses {
+                print_response_report(report);
+            }
+            if let Some(ref report) = result.spike {
+                print_spike_report(report);
+            }
+            if let Some(ref report) = result.closed_loop {
+                print_closed_loop_report(report);
+            }
+
+            results.push(result);
+        }
+
+        let sweep: Vec<&ClosedLoopReport> = results[first_result..]
+            .iter()
+            .filter_map(|result| result.closed_loop.as_ref())
+            .collect();
+        if sweep.len() > 1 {
+            print_user_sweep(&results[first_result].engine_label(), &sweep);
+        }
+    }
+
+    if results.len() > 1 {
+        print_run_comparison(&results);
+    }
+
+    schema.teardown()?;
 
     // Example: Update config at runtime
     println!("\n🔧 Runtime config update example:");
@@ -98,14 +293,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     println!("  Timeout:     {}ms", config.get_timeout_ms());
     println!("  Enabled:     {}", config.is_enabled());
 
-    // Graceful shutdown
-    println!("\n🛑 Shutting down...");
-    drop(tx);
-    for handle in handles {
-        let _ = handle.join();
-    }
-
-    println!("✅ Shutdown complete");
+    println!("\n✅ Shutdown complete");
 
     Ok(())
 }
@@ -6,6 +6,9 @@ pub struct Metrics {
     pub total_processed: u64,
     pub total_succeeded: u64,
     pub total_failed: u64,
+    // Written by successful requests
+    pub rows_written: u64,
+    pub bytes_written: u64,
 }
 
 pub struct MetricsCollector {
@@ -26,6 +29,13 @@ impl MetricsCollector {
         metrics.total_succeeded += 1;
     }
 
+    #[inline]
+    pub fn record_written(&self, rows: u64, bytes: u64) {
+        let mut metrics = self.metrics.lock();
+        metrics.rows_written += rows;
+        metrics.bytes_written += bytes;
+    }
+
     #[inline]
     pub fn record_failure(&self) {
         let mut metrics = self.metrics.lock();
@@ -33,6 +43,10 @@ impl MetricsCollector {
         metrics.total_failed += 1;
     }
 
+    pub fn reset(&self) {
+        *self.metrics.lock() = Metrics::default();
+    }
+
     pub fn get_snapshot(&self) -> Metrics {
         let metrics = self.metrics.lock();
         metrics.clone()
@@ -1,12 +1,35 @@
+use crossbeam_channel::Sender;
 use serde::{Deserialize, Serialize};
 
+use crate::workload::WorkloadKind;
+
 // Generic request type
 #[derive(Debug, Clone, Deserialize)]
 pub struct WorkRequest {
     pub id: String,
+    // Operation type and parameters carried over from a replayed trace
+    #[serde(default)]
+    pub operation: Option<WorkloadKind>,
+    #[serde(default)]
+    pub params: Vec<String>,
     // Add your request fields here
 }
 
+impl WorkRequest {
+    pub fn new(id: String) -> Self {
+        Self {
+            id,
+            operation: None,
+            params: Vec::new(),
+        }
+    }
+
+    // The value written as the row's id: a replayed first parameter, else the request id
+    pub fn row_id(&self) -> &str {
+        self.params.first().unwrap_or(&self.id)
+    }
+}
+
 // Lean response type - minimal allocations
 #[derive(Debug, Serialize)]
 pub struct WorkResponse {
@@ -14,6 +37,17 @@ pub struct WorkResponse {
     #[serde(skip_serializing_if = "String::is_empty")]
     pub id: String,
     pub status: ResponseStatus,
+    // Rows the request wrote, and the bytes of the values sent for them: each row's id
+    // text, whatever the workload. SQL text and COPY delimiters are not counted, and
+    // `created_at` is filled in by the server.
+    #[serde(skip_serializing_if = "is_zero")]
+    pub rows: u64,
+    #[serde(skip_serializing_if = "is_zero")]
+    pub bytes: u64,
+}
+
+fn is_zero(value: &u64) -> bool {
+    *value == 0
 }
 
 #[derive(Debug, Serialize, Clone, Copy)]
@@ -32,15 +66,60 @@ impl WorkResponse {
             success: true,
             id,
             status: ResponseStatus::Completed,
+            rows: 0,
+            bytes: 0,
         }
     }
 
+    #[inline]
+    pub fn written(mut self, rows: u64, bytes: u64) -> Self {
+        self.rows = rows;
+        self.bytes = bytes;
+        self
+    }
+
     #[inline]
     pub fn failure(id: String, status: ResponseStatus) -> Self {
         Self {
             success: false,
             id,
             status,
+            rows: 0,
+            bytes: 0,
         }
     }
 }
+
+// A response tagged with the sequence number of the envelope it answers
+#[derive(Debug)]
+pub struct Completion {
+    pub seq: u64,
+    pub response: WorkResponse,
+}
+
+/// A queued request plus the shared completion channel it answers on, keyed by `seq`.
+#[derive(Debug)]
+pub struct WorkEnvelope {
+    pub request: WorkRequest,
+    pub seq: u64,
+    pub reply_to: Sender<Completion>,
+}
+
+impl WorkEnvelope {
+    #[inline]
+    pub fn new(request: WorkRequest, seq: u64, reply_to: Sender<Completion>) -> Self {
+        Self {
+            request,
+            seq,
+            reply_to,
+        }
+    }
+
+    #[inline]
+    pub fn complete(&self, response: WorkResponse) {
+        let _ = self.reply_to.send(Completion {
+            seq: self.seq,
+            response,
+        });
+    }
+}
@@ -0,0 +1,273 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::shutdown::CancellationToken;
+
+// Shorter waits than this are not slept: `thread::sleep` would overshoot them, so the
+// next requests go out right away as a small batch instead
+const TIMER_RESOLUTION: Duration = Duration::from_millis(1);
+
+#[derive(Debug, Clone)]
+pub struct PacingReport {
+    // Requests that made it onto the queue
+    pub requests: u64,
+    // Where the schedule ended vs how long generation actually took
+    pub scheduled: Duration,
+    pub elapsed: Duration,
+    pub target_rps: f64,
+    pub achieved_rps: f64,
+    // Furthest behind schedule any send was
+    pub max_lag: Duration,
+    // Sends that went out without sleeping because they were due within timer resolution
+    pub batched: u64,
+    // Overload: sends dropped by the backpressure policy, and sends that found the queue
+    // full and had to wait
+    pub shed: u64,
+    pub blocked: u64,
+    pub blocked_time: Duration,
+    // One entry per generator thread when the load was split across several
+    pub per_generator: Vec<PacingReport>,
+}
+
+/// Keeps sends on an absolute schedule so overhead and sleep overshoot are made up on
+/// the following sends instead of adding up.
+pub struct Pacer {
+    start: Instant,
+    // Offset of the next send from `start`
+    scheduled: Duration,
+    max_lag: Duration,
+    batched: u64,
+    cancel: Option<CancellationToken>,
+}
+
+impl Pacer {
+    pub fn start() -> Self {
+        Self::start_at(Instant::now())
+    }
+
+    /// Starts a schedule at `start`, so several pacers can share one.
+    pub fn start_at(start: Instant) -> Self {
+        Self {
+            start,
+            scheduled: Duration::ZERO,
+            max_lag: Duration::ZERO,
+            batched: 0,
+            cancel: None,
+        }
+    }
+
+    /// Cuts any wait short once `cancel` fires, however long the gap.
+    pub fn with_cancel(mut self, cancel: CancellationToken) -> Self {
+        self.cancel = Some(cancel);
+        self
+    }
+
+    /// Waits until `gap` after the previous send's scheduled time.
+    #[inline]
+    pub fn pace(&mut self, gap: Duration) -> bool {
+        self.wait_until(self.scheduled + gap)
+    }
+
+    /// Waits until `offset` from the start of the schedule; false if cancelled first,
+    /// in which case the schedule ends where it was interrupted.
+    pub fn wait_until(&mut self, offset: Duration) -> bool {
+        self.scheduled = offset;
+        let due = self.start + offset;
+        let now = Instant::now();
+
+        if due > now {
+            let remaining = due - now;
+            if remaining < TIMER_RESOLUTION {
+                self.batched += 1;
+                return true;
+            }
+            if !self.sleep_until(due) {
+                self.scheduled = self.start.elapsed();
+                return false;
+            }
+        }
+
+        self.max_lag = self
+            .max_lag
+            .max(Instant::now().saturating_duration_since(due));
+        true
+    }
+
+    // Sleeps until `due`, checking for cancellation between slices
+    fn sleep_until(&self, due: Instant) -> bool {
+        match self.cancel {
+            Some(ref cancel) => cancel.sleep_until(due),
+            None => {
+                thread::sleep(due.saturating_duration_since(Instant::now()));
+                true
+            }
+        }
+    }
+
+    /// Reports on the schedule; `shed` sends were scheduled but never queued.
+    pub fn finish(&self, requests: u64, shed: u64) -> PacingReport {
+        let elapsed = self.start.elapsed();
+
+        PacingReport {
+            requests,
+            scheduled: self.scheduled,
+            elapsed,
+            target_rps: rate(requests + shed, self.scheduled),
+            achieved_rps: rate(requests, elapsed),
+            max_lag: self.max_lag,
+            batched: self.batched,
+            shed,
+            blocked: 0,
+            blocked_time: Duration::ZERO,
+            per_generator: Vec::new(),
+        }
+    }
+}
+
+impl PacingReport {
+    /// Totals the reports of generators that ran side by side on a shared schedule.
+    pub fn combine(mut reports: Vec<PacingReport>) -> PacingReport {
+        if reports.len() == 1 {
+            return reports.remove(0);
+        }
+
+        let requests = reports.iter().map(|r| r.requests).sum();
+        let scheduled = reports
+            .iter()
+            .map(|r| r.scheduled)
+            .max()
+            .unwrap_or_default();
+        let elapsed = reports.iter().map(|r| r.elapsed).max().unwrap_or_default();
+        let shed = reports.iter().map(|r| r.shed).sum();
+
+        PacingReport {
+            requests,
+            scheduled,
+            elapsed,
+            target_rps: rate(requests + shed, scheduled),
+            achieved_rps: rate(requests, elapsed),
+            max_lag: reports.iter().map(|r| r.max_lag).max().unwrap_or_default(),
+            batched: reports.iter().map(|r| r.batched).sum(),
+            shed,
+            blocked: reports.iter().map(|r| r.blocked).sum(),
+            blocked_time: reports.iter().map(|r| r.blocked_time).sum(),
+            per_generator: reports,
+        }
+    }
+}
+
+fn rate(requests: u64, span: Duration) -> f64 {
+    if span.is_zero() {
+        0.0
+    } else {
+        requests as f64 / span.as_secs_f64()
+    }
+}
+
+pub fn print_pacing_report(report: &PacingReport) {
+    let accuracy = if report.target_rps > 0.0 {
+        report.achieved_rps / report.target_rps * 100.0
+    } else {
+        0.0
+    };
+
+    println!("\n🎯 Pacing:");
+    println!("  Requests Sent:       {:>10}", report.requests);
+    println!("  Target Rate:         {:>10.2} rps", report.target_rps);
+    println!(
+        "  Achieved Rate:       {:>10.2} rps ({:.1}%)",
+        report.achieved_rps, accuracy
+    );
+    println!(
+        "  Schedule Drift:      {:>10.3} ms",
+        (report.elapsed.as_secs_f64() - report.scheduled.as_secs_f64()) * 1000.0
+    );
+    println!(
+        "  Max Lag:             {:>10.3} ms",
+        report.max_lag.as_secs_f64() * 1000.0
+    );
+    println!("  Batched Sends:       {:>10}", report.batched);
+    println!("  Shed:                {:>10}", report.shed);
+    println!("  Blocked Sends:       {:>10}", report.blocked);
+    println!(
+        "  Time Blocked:        {:>10.3} ms",
+        report.blocked_time.as_secs_f64() * 1000.0
+    );
+
+    if report.per_generator.len() > 1 {
+        println!(
+            "\n  {:>9} {:>10} {:>12} {:>12} {:>10} {:>8} {:>8}",
+            "Generator", "Requests", "Target rps", "Achieved", "Max lag ms", "Shed", "Blocked"
+        );
+        for (index, generator) in report.per_generator.iter().enumerate() {
+            println!(
+                "  {:>9} {:>10} {:>12.2} {:>12.2} {:>10.3} {:>8} {:>8}",
+                index,
+                generator.requests,
+                generator.target_rps,
+                generator.achieved_rps,
+                generator.max_lag.as_secs_f64() * 1000.0,
+                generator.shed,
+                generator.blocked
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GAP: Duration = Duration::from_millis(10);
+
+    #[test]
+    fn overhead_is_made_up_on_later_sends() {
+        let mut pacer = Pacer::start();
+
+        for i in 0..10 {
+            assert!(pacer.pace(GAP));
+            // A slow send is absorbed by the following gaps instead of adding up
+            if i == 2 {
+                thread::sleep(GAP + GAP / 2);
+            }
+        }
+        let report = pacer.finish(10, 0);
+
+        assert_eq!(report.scheduled, GAP * 10);
+        assert!(report.elapsed >= GAP * 10);
+        assert!(report.elapsed < GAP * 13, "took {:?}", report.elapsed);
+        assert!(report.max_lag >= GAP / 2, "max lag {:?}", report.max_lag);
+    }
+
+    #[test]
+    fn sends_due_within_timer_resolution_go_out_together() {
+        // Every send below is due less than a millisecond from now
+        let mut pacer = Pacer::start_at(Instant::now() + Duration::from_micros(500));
+
+        for offset in [0, 100, 200] {
+            assert!(pacer.wait_until(Duration::from_micros(offset)));
+        }
+
+        assert_eq!(pacer.finish(3, 0).batched, 3);
+    }
+
+    #[test]
+    fn cancelled_pacer_stops_waiting_promptly() {
+        let cancel = CancellationToken::new();
+        let mut pacer = Pacer::start().with_cancel(cancel.clone());
+
+        let canceller = thread::spawn(move || {
+            thread::sleep(Duration::from_millis(20));
+            cancel.cancel();
+        });
+        let start = Instant::now();
+        assert!(!pacer.wait_until(Duration::from_secs(60)));
+        canceller.join().unwrap();
+
+        assert!(start.elapsed() < Duration::from_secs(1));
+        // The schedule ends where it was interrupted, not at the far-off send
+        assert!(pacer.finish(0, 0).scheduled < Duration::from_secs(1));
+        // Already cancelled: no wait at all
+        assert!(!pacer.pace(Duration::from_secs(60)));
+    }
+}
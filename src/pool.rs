@@ -1,16 +1,165 @@
-use crate::error::PoolError;
+use crate::error::{PoolError, WorkerError};
+use crate::settings::PoolSettings;
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
-use diesel::r2d2::{ConnectionManager, Pool};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
+use diesel::sql_types::Text;
+use serde::Deserialize;
+use std::fmt;
+use std::sync::Arc;
 
 pub type DbPool = Pool<ConnectionManager<PgConnection>>;
 pub type DbConnection = diesel::r2d2::PooledConnection<ConnectionManager<PgConnection>>;
 
-pub fn create_pool(database_url: &str, worker_count: usize) -> Result<DbPool, PoolError> {
+pub fn create_pool(
+    database_url: &str,
+    worker_count: usize,
+    settings: &PoolSettings,
+) -> Result<DbPool, PoolError> {
     let manager = ConnectionManager::<PgConnection>::new(database_url);
     Pool::builder()
         .max_size((worker_count + 2) as u32)
         .connection_timeout(std::time::Duration::from_secs(5))
         .test_on_check_out(true)
+        .connection_customizer(Box::new(SessionCustomizer::new(settings)?))
         .build(manager)
         .map_err(PoolError::from)
 }
+
+/// How a worker obtains the connection it runs a request on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionStrategy {
+    // One pooled connection checked out for the worker's whole life
+    Cached,
+    // A pooled connection checked out and returned for every request
+    PerRequest,
+    // A fresh connection established for every request, bypassing the pool
+    Dedicated,
+}
+
+impl fmt::Display for ConnectionStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionStrategy::Cached => f.write_str("cached"),
+            ConnectionStrategy::PerRequest => f.write_str("per_request"),
+            ConnectionStrategy::Dedicated => f.write_str("dedicated"),
+        }
+    }
+}
+
+/// Everything a worker needs to get connections under a given strategy.
+#[derive(Clone)]
+pub struct ConnectionSource {
+    pub pool: DbPool,
+    pub strategy: ConnectionStrategy,
+    database_url: Arc<str>,
+    customizer: Arc<SessionCustomizer>,
+}
+
+impl ConnectionSource {
+    pub fn new(
+        pool: DbPool,
+        strategy: ConnectionStrategy,
+        database_url: &str,
+        settings: &PoolSettings,
+    ) -> Result<Self, PoolError> {
+        Ok(Self {
+            pool,
+            strategy,
+            database_url: Arc::from(database_url),
+            customizer: Arc::new(SessionCustomizer::new(settings)?),
+        })
+    }
+
+    pub fn with_strategy(&self, strategy: ConnectionStrategy) -> Self {
+        Self {
+            strategy,
+            ..self.clone()
+        }
+    }
+
+    pub fn database_url(&self) -> &str {
+        &self.database_url
+    }
+
+    pub fn session(&self) -> &SessionCustomizer {
+        &self.customizer
+    }
+
+    /// Opens a connection outside the pool, applying the same session settings.
+    pub fn establish(&self) -> Result<PgConnection, WorkerError> {
+        let mut conn = PgConnection::establish(&self.database_url)?;
+        self.customizer.apply(&mut conn)?;
+        Ok(conn)
+    }
+}
+
+// Session settings go through `set_config` with bound values, so the server parses
+// each value like `SET` would (list settings such as `search_path` included)
+pub const SET_CONFIG_SQL: &str = "SELECT set_config($1, $2, false)";
+
+/// Applies the configured session settings and init SQL on every new pooled connection.
+#[derive(Debug)]
+pub struct SessionCustomizer {
+    settings: Vec<(String, String)>,
+    init_sql: Vec<String>,
+}
+
+impl SessionCustomizer {
+    pub fn new(settings: &PoolSettings) -> Result<Self, PoolError> {
+        let mut session_settings = Vec::with_capacity(settings.session_settings.len());
+
+        for setting in &settings.session_settings {
+            if !is_valid_setting_name(&setting.name) {
+                return Err(PoolError::InvalidSessionSetting(setting.name.clone()));
+            }
+            session_settings.push((setting.name.clone(), setting.value.clone()));
+        }
+
+        Ok(Self {
+            settings: session_settings,
+            init_sql: settings.init_sql.clone(),
+        })
+    }
+
+    /// `(name, value)` pairs, to be applied in order with `SET_CONFIG_SQL`.
+    pub fn settings(&self) -> &[(String, String)] {
+        &self.settings
+    }
+
+    /// Run after the settings.
+    pub fn init_sql(&self) -> &[String] {
+        &self.init_sql
+    }
+
+    pub fn apply(&self, conn: &mut PgConnection) -> QueryResult<()> {
+        for (name, value) in &self.settings {
+            diesel::sql_query(SET_CONFIG_SQL)
+                .bind::<Text, _>(name)
+                .bind::<Text, _>(value)
+                .execute(conn)?;
+        }
+
+        for statement in &self.init_sql {
+            conn.batch_execute(statement)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CustomizeConnection<PgConnection, diesel::r2d2::Error> for SessionCustomizer {
+    fn on_acquire(&self, conn: &mut PgConnection) -> Result<(), diesel::r2d2::Error> {
+        self.apply(conn).map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+// GUC names are identifiers, optionally qualified with a dot (e.g. `auto_explain.log_min_duration`)
+fn is_valid_setting_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.')
+}
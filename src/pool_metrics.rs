@@ -0,0 +1,222 @@
+use crossbeam_channel::{RecvTimeoutError, Sender};
+use parking_lot::Mutex;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::benchmark::latency_percentile;
+use crate::pool::DbPool;
+
+// Maximum number of pool state rows printed in the report
+const MAX_REPORTED_SAMPLES: usize = 20;
+
+#[derive(Debug, Clone, Copy)]
+pub struct PoolStateSample {
+    pub elapsed: Duration,
+    pub connections: u32,
+    pub idle_connections: u32,
+}
+
+#[derive(Default, Clone)]
+pub struct PoolMetrics {
+    pub checkout_waits: Vec<Duration>,
+    pub acquire_failures: u64,
+    pub reconnects: BTreeMap<usize, u64>,
+    pub state_samples: Vec<PoolStateSample>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PoolStats {
+    pub checkouts: u64,
+    pub acquire_failures: u64,
+    pub min_checkout_wait: Duration,
+    pub max_checkout_wait: Duration,
+    pub avg_checkout_wait: Duration,
+    pub p50_checkout_wait: Duration,
+    pub p99_checkout_wait: Duration,
+    pub total_reconnects: u64,
+    pub reconnects: BTreeMap<usize, u64>,
+    pub state_samples: Vec<PoolStateSample>,
+}
+
+pub struct PoolMetricsCollector {
+    metrics: Arc<Mutex<PoolMetrics>>,
+    // State samples are timed from here
+    start_time: Arc<Mutex<Instant>>,
+}
+
+impl PoolMetricsCollector {
+    pub fn new() -> Self {
+        Self {
+            metrics: Arc::new(Mutex::new(PoolMetrics::default())),
+            start_time: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    #[inline]
+    pub fn record_checkout(&self, wait: Duration, success: bool) {
+        let mut metrics = self.metrics.lock();
+        metrics.checkout_waits.push(wait);
+        if !success {
+            metrics.acquire_failures += 1;
+        }
+    }
+
+    #[inline]
+    pub fn record_reconnect(&self, worker_id: usize) {
+        *self.metrics.lock().reconnects.entry(worker_id).or_insert(0) += 1;
+    }
+
+    pub fn record_state(&self, connections: u32, idle_connections: u32) {
+        let elapsed = self.start_time.lock().elapsed();
+        self.metrics.lock().state_samples.push(PoolStateSample {
+            elapsed,
+            connections,
+            idle_connections,
+        });
+    }
+
+    pub fn reset(&self) {
+        *self.start_time.lock() = Instant::now();
+        *self.metrics.lock() = PoolMetrics::default();
+    }
+
+    pub fn get_snapshot(&self) -> PoolMetrics {
+        self.metrics.lock().clone()
+    }
+
+    pub fn get_stats(&self) -> PoolStats {
+        let PoolMetrics {
+            mut checkout_waits,
+            acquire_failures,
+            reconnects,
+            state_samples,
+        } = self.get_snapshot();
+
+        checkout_waits.sort();
+
+        let avg_checkout_wait = if checkout_waits.is_empty() {
+            Duration::ZERO
+        } else {
+            Duration::from_nanos(
+                checkout_waits
+                    .iter()
+                    .map(|d| d.as_nanos() as u64)
+                    .sum::<u64>()
+                    / checkout_waits.len() as u64,
+            )
+        };
+
+        PoolStats {
+            checkouts: checkout_waits.len() as u64,
+            acquire_failures,
+            min_checkout_wait: checkout_waits.first().copied().unwrap_or_default(),
+            max_checkout_wait: checkout_waits.last().copied().unwrap_or_default(),
+            avg_checkout_wait,
+            p50_checkout_wait: latency_percentile(&checkout_waits, 0.50),
+            p99_checkout_wait: latency_percentile(&checkout_waits, 0.99),
+            total_reconnects: reconnects.values().sum(),
+            reconnects,
+            state_samples,
+        }
+    }
+
+    pub fn clone_handle(&self) -> Self {
+        Self {
+            metrics: Arc::clone(&self.metrics),
+            start_time: Arc::clone(&self.start_time),
+        }
+    }
+}
+
+impl Default for PoolMetricsCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Background thread that records `DbPool::state()` at a fixed interval.
+pub struct PoolSampler {
+    stop_tx: Sender<()>,
+    handle: thread::JoinHandle<()>,
+}
+
+impl PoolSampler {
+    pub fn stop(self) {
+        let _ = self.stop_tx.send(());
+        let _ = self.handle.join();
+    }
+}
+
+pub fn spawn_pool_sampler(
+    pool: DbPool,
+    interval: Duration,
+    metrics: PoolMetricsCollector,
+) -> PoolSampler {
+    let (stop_tx, stop_rx) = crossbeam_channel::bounded::<()>(1);
+
+    let handle = thread::Builder::new()
+        .name("pool-sampler".to_string())
+        .spawn(move || loop {
+            let state = pool.state();
+            metrics.record_state(state.connections, state.idle_connections);
+
+            match stop_rx.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => continue,
+                _ => break,
+            }
+        })
+        .expect("Failed to spawn pool sampler thread");
+
+    PoolSampler { stop_tx, handle }
+}
+
+pub fn print_pool_report(stats: &PoolStats) {
+    println!("\n🔌 Connection Pool:");
+    println!("  Checkouts:           {:>10}", stats.checkouts);
+    println!("  Acquire Failures:    {:>10}", stats.acquire_failures);
+    println!("  Reconnects:          {:>10}", stats.total_reconnects);
+    println!(
+        "  Min Checkout Wait:   {:>10.3} ms",
+        stats.min_checkout_wait.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  Avg Checkout Wait:   {:>10.3} ms",
+        stats.avg_checkout_wait.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  P50 Checkout Wait:   {:>10.3} ms",
+        stats.p50_checkout_wait.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  P99 Checkout Wait:   {:>10.3} ms",
+        stats.p99_checkout_wait.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  Max Checkout Wait:   {:>10.3} ms",
+        stats.max_checkout_wait.as_secs_f64() * 1000.0
+    );
+
+    if !stats.reconnects.is_empty() {
+        println!("\n  Reconnects per worker:");
+        for (worker_id, count) in &stats.reconnects {
+            println!("    worker-{:<12} {:>10}", worker_id, count);
+        }
+    }
+
+    if !stats.state_samples.is_empty() {
+        println!("\n  Pool state over time:");
+        println!("    {:>9}  {:>11}  {:>6}", "t (s)", "connections", "idle");
+
+        let step = stats.state_samples.len().div_ceil(MAX_REPORTED_SAMPLES);
+        for sample in stats.state_samples.iter().step_by(step) {
+            println!(
+                "    {:>9.2}  {:>11}  {:>6}",
+                sample.elapsed.as_secs_f64(),
+                sample.connections,
+                sample.idle_connections
+            );
+        }
+    }
+}
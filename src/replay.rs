@@ -0,0 +1,202 @@
+use serde::Deserialize;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::error::TraceError;
+use crate::workload::WorkloadKind;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ReplaySettings {
+    // Trace file to replay; when set it replaces the load pattern
+    pub trace: Option<PathBuf>,
+    // 2.0 replays twice as fast as recorded, 0.5 at half speed
+    pub speed: f64,
+}
+
+impl Default for ReplaySettings {
+    fn default() -> Self {
+        Self {
+            trace: None,
+            speed: 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ReplayEvent {
+    // Offset from the first event in the trace
+    pub offset: Duration,
+    // Workload kind to run instead of the configured one
+    pub operation: Option<WorkloadKind>,
+    // The first one is the row id to write instead of the request id
+    pub params: Vec<String>,
+}
+
+/// Recorded requests, one `timestamp[,operation[,param...]]` per line with timestamps in
+/// seconds, re-issued in timestamp order with their original relative timing.
+pub struct ReplayTrace {
+    pub source: String,
+    pub events: Vec<ReplayEvent>,
+}
+
+impl ReplayTrace {
+    pub fn from_file(path: &Path) -> Result<Self, TraceError> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::parse(&path.display().to_string(), &contents)
+    }
+
+    pub fn parse(source: &str, contents: &str) -> Result<Self, TraceError> {
+        let mut records = Vec::new();
+
+        for (index, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let mut fields = line.split(',').map(str::trim);
+            let timestamp = fields.next().unwrap_or_default();
+            let timestamp: f64 = match timestamp.parse() {
+                Ok(ts) if f64::is_finite(ts) => ts,
+                _ => {
+                    return Err(TraceError::Parse {
+                        line: index + 1,
+                        reason: format!("invalid timestamp {:?}", timestamp),
+                    })
+                }
+            };
+
+            let operation = match fields.next().filter(|op| !op.is_empty()) {
+                Some(op) => Some(op.parse().map_err(|reason| TraceError::Parse {
+                    line: index + 1,
+                    reason,
+                })?),
+                None => None,
+            };
+            let params = fields.map(str::to_string).collect();
+            records.push((timestamp, operation, params));
+        }
+
+        if records.is_empty() {
+            return Err(TraceError::Empty);
+        }
+
+        // Log exports are not always strictly ordered
+        records.sort_by(|a, b| a.0.total_cmp(&b.0));
+        let origin = records[0].0;
+
+        let events = records
+            .into_iter()
+            .map(|(timestamp, operation, params)| ReplayEvent {
+                offset: Duration::from_secs_f64(timestamp - origin),
+                operation,
+                params,
+            })
+            .collect();
+
+        Ok(Self {
+            source: source.to_string(),
+            events,
+        })
+    }
+
+    /// Time between the first and the last event, as recorded.
+    pub fn duration(&self) -> Duration {
+        self.events
+            .last()
+            .map_or(Duration::ZERO, |event| event.offset)
+    }
+}
+
+// Traces can hold millions of events; keep them out of pattern and mode output
+impl fmt::Debug for ReplayTrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} ({} events over {:.1}s)",
+            self.source,
+            self.events.len(),
+            self.duration().as_secs_f64()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn offsets(trace: &ReplayTrace) -> Vec<f64> {
+        trace
+            .events
+            .iter()
+            .map(|event| event.offset.as_secs_f64())
+            .collect()
+    }
+
+    #[test]
+    fn unsorted_events_are_replayed_in_timestamp_order() {
+        let trace = ReplayTrace::parse("test", "102.5\n100\n101,insert,a,b\n").unwrap();
+
+        assert_eq!(offsets(&trace), vec![0.0, 1.0, 2.5]);
+        assert_eq!(trace.duration(), Duration::from_secs_f64(2.5));
+    }
+
+    #[test]
+    fn operation_and_params_are_carried_per_event() {
+        let trace = ReplayTrace::parse("test", "1,COPY,row-1,x\n0,,row-0\n2\n").unwrap();
+        let events = &trace.events;
+
+        assert_eq!(events[0].operation, None);
+        assert_eq!(events[0].params, vec!["row-0"]);
+        assert_eq!(events[1].operation, Some(WorkloadKind::Copy));
+        assert_eq!(events[1].params, vec!["row-1", "x"]);
+        assert_eq!(events[2].operation, None);
+        assert!(events[2].params.is_empty());
+    }
+
+    #[test]
+    fn unknown_operation_reports_its_line() {
+        match ReplayTrace::parse("test", "1,insert\n2,delete\n") {
+            Err(TraceError::Parse { line, reason }) => {
+                assert_eq!(line, 2);
+                assert!(reason.contains("\"delete\""), "{}", reason);
+            }
+            other => panic!("expected a parse error, got {:?}", other.err()),
+        }
+    }
+
+    #[test]
+    fn comments_and_blank_lines_are_skipped() {
+        let trace = ReplayTrace::parse("test", "# exported\n\n  10\n   \n# 5\n11\n").unwrap();
+
+        assert_eq!(offsets(&trace), vec![0.0, 1.0]);
+    }
+
+    #[test]
+    fn bad_timestamp_reports_its_line() {
+        let error = ReplayTrace::parse("test", "# header\n10\nsoon,insert\n").err();
+
+        match error {
+            Some(TraceError::Parse { line, reason }) => {
+                assert_eq!(line, 3);
+                assert!(reason.contains("\"soon\""), "{}", reason);
+            }
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+        assert!(ReplayTrace::parse("test", "inf\n").is_err());
+    }
+
+    #[test]
+    fn trace_without_events_is_rejected() {
+        assert!(matches!(
+            ReplayTrace::parse("test", ""),
+            Err(TraceError::Empty)
+        ));
+        assert!(matches!(
+            ReplayTrace::parse("test", "# only a comment\n\n"),
+            Err(TraceError::Empty)
+        ));
+    }
+}
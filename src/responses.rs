@@ -0,0 +1,332 @@
+use crossbeam_channel::{Receiver, Sender};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::benchmark::{BenchmarkCollector, BenchmarkStats};
+use crate::models::{Completion, WorkEnvelope, WorkRequest};
+
+enum Slot {
+    // Handed to the work queue at this instant, no response yet
+    Pending(Instant),
+    Completed,
+    Cancelled,
+}
+
+// Slots are indexed by envelope sequence number
+struct Tracking {
+    slots: Vec<Slot>,
+    outstanding: u64,
+    dispatched: u64,
+    received: u64,
+    duplicate_responses: u64,
+    unknown_responses: u64,
+    started: Instant,
+    timeline: Vec<LatencySample>,
+}
+
+// One matched response, placed on the run's timeline by when it was dispatched
+#[derive(Debug, Clone, Copy)]
+pub struct LatencySample {
+    pub dispatched: Duration,
+    pub latency: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResponseReport {
+    pub dispatched: u64,
+    pub received: u64,
+    pub matched: u64,
+    pub lost: u64,
+    pub duplicate_responses: u64,
+    pub unknown_responses: u64,
+    // Caller-side latency: from dispatch until the response arrived back
+    pub round_trip: BenchmarkStats,
+    // In completion order, not dispatch order
+    pub timeline: Vec<LatencySample>,
+}
+
+/// Generator-side handle used to register requests as they are dispatched.
+pub struct ResponseTracker {
+    tracking: Arc<Mutex<Tracking>>,
+    completion_tx: Sender<Completion>,
+}
+
+impl ResponseTracker {
+    /// Records the request as in flight and wraps it in an envelope that completes
+    /// into the shared completion channel.
+    #[inline]
+    pub fn envelope(&self, request: WorkRequest) -> WorkEnvelope {
+        let mut tracking = self.tracking.lock();
+        let seq = tracking.slots.len() as u64;
+        tracking.slots.push(Slot::Pending(Instant::now()));
+        tracking.dispatched += 1;
+        tracking.outstanding += 1;
+
+        WorkEnvelope::new(request, seq, self.completion_tx.clone())
+    }
+
+    /// Forgets a registered request that never made it onto the work queue.
+    #[inline]
+    pub fn cancel(&self, seq: u64) {
+        let mut tracking = self.tracking.lock();
+        if let Some(slot @ Slot::Pending(_)) = tracking.slots.get_mut(seq as usize) {
+            *slot = Slot::Cancelled;
+            tracking.dispatched -= 1;
+            tracking.outstanding -= 1;
+        }
+    }
+
+    pub fn outstanding(&self) -> u64 {
+        self.tracking.lock().outstanding
+    }
+
+    pub fn clone_handle(&self) -> Self {
+        Self {
+            tracking: Arc::clone(&self.tracking),
+            completion_tx: self.completion_tx.clone(),
+        }
+    }
+}
+
+/// Receives the completion of every request registered through its tracker,
+/// matching each one back to its envelope.
+pub struct ResponseCollector {
+    tracker: ResponseTracker,
+    round_trip: BenchmarkCollector,
+    handle: thread::JoinHandle<()>,
+}
+
+impl ResponseCollector {
+    /// Starts the collector with slots pre-allocated for `expected_requests`.
+    pub fn start(expected_requests: u64) -> Self {
+        let (completion_tx, completion_rx) = crossbeam_channel::unbounded();
+        let tracking = Arc::new(Mutex::new(Tracking {
+            slots: Vec::with_capacity(expected_requests as usize),
+            outstanding: 0,
+            dispatched: 0,
+            received: 0,
+            duplicate_responses: 0,
+            unknown_responses: 0,
+            started: Instant::now(),
+            timeline: Vec::with_capacity(expected_requests as usize),
+        }));
+        let round_trip = BenchmarkCollector::new();
+
+        let handle = {
+            let tracking = Arc::clone(&tracking);
+            let round_trip = round_trip.clone_handle();
+
+            thread::Builder::new()
+                .name("response-collector".to_string())
+                .spawn(move || collect_completions(completion_rx, tracking, round_trip))
+                .expect("Failed to spawn response collector thread")
+        };
+
+        Self {
+            tracker: ResponseTracker {
+                tracking,
+                completion_tx,
+            },
+            round_trip,
+            handle,
+        }
+    }
+
+    pub fn tracker(&self) -> ResponseTracker {
+        self.tracker.clone_handle()
+    }
+
+    /// Call after the workers have exited; anything still pending counts as lost.
+    pub fn finish(self) -> ResponseReport {
+        let Self {
+            tracker,
+            round_trip,
+            handle,
+        } = self;
+        let tracking = Arc::clone(&tracker.tracking);
+        drop(tracker);
+        let _ = handle.join();
+
+        let mut tracking = tracking.lock();
+        let round_trip = round_trip.get_stats();
+
+        ResponseReport {
+            dispatched: tracking.dispatched,
+            received: tracking.received,
+            matched: round_trip.total_requests,
+            lost: tracking.outstanding,
+            duplicate_responses: tracking.duplicate_responses,
+            unknown_responses: tracking.unknown_responses,
+            round_trip,
+            timeline: std::mem::take(&mut tracking.timeline),
+        }
+    }
+}
+
+fn collect_completions(
+    completion_rx: Receiver<Completion>,
+    tracking: Arc<Mutex<Tracking>>,
+    round_trip: BenchmarkCollector,
+) {
+    while let Ok(completion) = completion_rx.recv() {
+        let mut tracking = tracking.lock();
+        tracking.received += 1;
+
+        let seq = completion.seq as usize;
+        match tracking.slots.get(seq) {
+            Some(&Slot::Pending(dispatched_at)) => {
+                let latency = dispatched_at.elapsed();
+                round_trip.record_request(latency, completion.response.success);
+
+                let dispatched = dispatched_at.saturating_duration_since(tracking.started);
+                tracking.timeline.push(LatencySample {
+                    dispatched,
+                    latency,
+                });
+                tracking.slots[seq] = Slot::Completed;
+                tracking.outstanding -= 1;
+            }
+            Some(Slot::Completed) => tracking.duplicate_responses += 1,
+            Some(Slot::Cancelled) | None => tracking.unknown_responses += 1,
+        }
+    }
+}
+
+pub fn print_response_report(report: &ResponseReport) {
+    println!("\n📬 Responses (caller side):");
+    println!("  Dispatched:          {:>10}", report.dispatched);
+    println!("  Received:            {:>10}", report.received);
+    println!("  Matched:             {:>10}", report.matched);
+    println!("  Lost:                {:>10}", report.lost);
+    println!("  Duplicate Responses: {:>10}", report.duplicate_responses);
+    println!("  Unknown Responses:   {:>10}", report.unknown_responses);
+    println!(
+        "  Avg Round Trip:      {:>10.3} ms",
+        report.round_trip.avg_latency.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  P50 Round Trip:      {:>10.3} ms",
+        report.round_trip.p50_latency.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  P99 Round Trip:      {:>10.3} ms",
+        report.round_trip.p99_latency.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  Max Round Trip:      {:>10.3} ms",
+        report.round_trip.max_latency.as_secs_f64() * 1000.0
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::WorkResponse;
+
+    fn request(id: &str) -> WorkRequest {
+        WorkRequest::new(id.to_string())
+    }
+
+    fn success(envelope: &WorkEnvelope) {
+        envelope.complete(WorkResponse::success(envelope.request.id.clone()));
+    }
+
+    #[test]
+    fn every_response_matched_once() {
+        let collector = ResponseCollector::start(2);
+        let tracker = collector.tracker();
+        let envelopes = [
+            tracker.envelope(request("a")),
+            tracker.envelope(request("b")),
+        ];
+        assert_eq!(tracker.outstanding(), 2);
+
+        envelopes.iter().for_each(success);
+        drop((tracker, envelopes));
+        let report = collector.finish();
+
+        assert_eq!(report.dispatched, 2);
+        assert_eq!(report.received, 2);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.lost, 0);
+        assert_eq!(report.timeline.len(), 2);
+    }
+
+    #[test]
+    fn second_response_for_a_seq_is_a_duplicate() {
+        let collector = ResponseCollector::start(1);
+        let tracker = collector.tracker();
+        let envelope = tracker.envelope(request("a"));
+
+        success(&envelope);
+        success(&envelope);
+        drop((tracker, envelope));
+        let report = collector.finish();
+
+        assert_eq!(report.received, 2);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.duplicate_responses, 1);
+        assert_eq!(report.unknown_responses, 0);
+    }
+
+    #[test]
+    fn cancel_takes_the_request_out_of_dispatched() {
+        let collector = ResponseCollector::start(2);
+        let tracker = collector.tracker();
+        let kept = tracker.envelope(request("a"));
+        let cancelled = tracker.envelope(request("b"));
+
+        tracker.cancel(cancelled.seq);
+        assert_eq!(tracker.outstanding(), 1);
+        // Cancelling twice, or a seq never handed out, changes nothing
+        tracker.cancel(cancelled.seq);
+        tracker.cancel(99);
+        assert_eq!(tracker.outstanding(), 1);
+
+        success(&kept);
+        drop((tracker, kept, cancelled));
+        let report = collector.finish();
+
+        assert_eq!(report.dispatched, 1);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.lost, 0);
+    }
+
+    #[test]
+    fn late_answer_to_a_cancelled_seq_is_unknown() {
+        let collector = ResponseCollector::start(1);
+        let tracker = collector.tracker();
+        let envelope = tracker.envelope(request("a"));
+
+        tracker.cancel(envelope.seq);
+        success(&envelope);
+        let stray = WorkEnvelope::new(request("b"), 99, tracker.completion_tx.clone());
+        success(&stray);
+        drop((tracker, envelope, stray));
+        let report = collector.finish();
+
+        assert_eq!(report.dispatched, 0);
+        assert_eq!(report.received, 2);
+        assert_eq!(report.matched, 0);
+        assert_eq!(report.unknown_responses, 2);
+        assert_eq!(report.lost, 0);
+    }
+
+    #[test]
+    fn unanswered_request_is_lost_after_finish() {
+        let collector = ResponseCollector::start(2);
+        let tracker = collector.tracker();
+        let answered = tracker.envelope(request("a"));
+        let unanswered = tracker.envelope(request("b"));
+
+        success(&answered);
+        drop((tracker, answered, unanswered));
+        let report = collector.finish();
+
+        assert_eq!(report.dispatched, 2);
+        assert_eq!(report.matched, 1);
+        assert_eq!(report.lost, 1);
+    }
+}
@@ -0,0 +1,441 @@
+use crossbeam_channel::bounded;
+use serde::Deserialize;
+use std::fmt;
+use std::time::Duration;
+
+use crate::arrival::ArrivalSettings;
+use crate::async_engine::spawn_async_engine;
+use crate::benchmark::{BenchmarkCollector, BenchmarkStats};
+use crate::closed_loop::{run_virtual_users, ClosedLoopReport};
+use crate::error::EngineError;
+use crate::load_gen::{spawn_load_generator, BackpressureSettings, GeneratorOptions, LoadPattern};
+use crate::metrics::{Metrics, MetricsCollector};
+use crate::models::{WorkEnvelope, WorkRequest};
+use crate::pacer::PacingReport;
+use crate::pool::{ConnectionSource, ConnectionStrategy};
+use crate::pool_metrics::{spawn_pool_sampler, PoolMetricsCollector, PoolStats};
+use crate::responses::{ResponseCollector, ResponseReport, ResponseTracker};
+use crate::seed::RunSeed;
+use crate::settings::{EngineSettings, WarmupSettings};
+use crate::shutdown::CancellationToken;
+use crate::spike::{analyze_spike, SpikeRecovery};
+use crate::worker::{spawn_worker_pool_with_metrics, WorkerOptions};
+use crate::workload::Workload;
+
+/// Which execution model serves the work queue.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EngineKind {
+    // One OS thread per worker blocking on a diesel connection
+    Threaded,
+    // Tasks on a tokio runtime over pipelined tokio-postgres clients
+    Async,
+}
+
+impl fmt::Display for EngineKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            EngineKind::Threaded => f.write_str("threaded"),
+            EngineKind::Async => f.write_str("async"),
+        }
+    }
+}
+
+/// How requests are fed into the work queue.
+#[derive(Debug, Clone)]
+pub enum LoadMode {
+    // Rate-driven: requests are sent on the pattern's schedule regardless of responses
+    Open(LoadPattern),
+    // N virtual users, each waiting for its response before sending the next request
+    Closed { users: usize, think_time: Duration },
+}
+
+impl fmt::Display for LoadMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadMode::Open(pattern) => write!(f, "{:?}", pattern),
+            LoadMode::Closed { users, think_time } => write!(
+                f,
+                "Closed loop {{ users: {}, think_time_ms: {} }}",
+                users,
+                think_time.as_millis()
+            ),
+        }
+    }
+}
+
+pub struct RunOptions {
+    pub worker_count: usize,
+    pub total_requests: u64,
+    pub mode: LoadMode,
+    // What workers do with each request; the async engine only inserts
+    pub workload: Workload,
+    pub pool_sample_interval: Duration,
+    pub engine: EngineSettings,
+    pub warmup: WarmupSettings,
+    // How requests are spread around the pattern's rate
+    pub arrival: ArrivalSettings,
+    // Open-loop generator threads the rate is split across
+    pub generators: usize,
+    // What open-loop generators do when the work queue is full
+    pub backpressure: BackpressureSettings,
+    // Every generator and worker RNG stream derives from this
+    pub seed: RunSeed,
+    // How long to wait for outstanding responses once load generation ends
+    pub drain_timeout: Duration,
+    // Shorter drain limit that applies once the run has been interrupted
+    pub shutdown_grace: Duration,
+    pub cancel: CancellationToken,
+}
+
+impl RunOptions {
+    fn generator_options(&self) -> GeneratorOptions {
+        GeneratorOptions {
+            generators: self.generators,
+            arrivals: self.arrival,
+            backpressure: self.backpressure,
+            seed: self.seed,
+        }
+    }
+
+    fn worker_options(&self) -> WorkerOptions {
+        WorkerOptions {
+            seed: self.seed,
+            workload: self.workload,
+        }
+    }
+}
+
+pub struct RunResult {
+    pub engine: EngineKind,
+    pub strategy: ConnectionStrategy,
+    pub workload: Workload,
+    pub generation_time: Duration,
+    // Target vs achieved send rate; open-loop only
+    pub pacing: Option<PacingReport>,
+    pub metrics: Metrics,
+    pub stats: BenchmarkStats,
+    pub pool_stats: PoolStats,
+    pub closed_loop: Option<ClosedLoopReport>,
+    pub responses: Option<ResponseReport>,
+    // Only for spike patterns
+    pub spike: Option<SpikeRecovery>,
+    pub warmup: Option<BenchmarkStats>,
+    // Requests still queued when the drain timeout expired, discarded unprocessed
+    pub abandoned: u64,
+    // The run was interrupted before its load finished
+    pub partial: bool,
+}
+
+impl RunResult {
+    // Identifies the engine/connection setup and workload, independent of the load mode
+    pub fn engine_label(&self) -> String {
+        let engine = match self.engine {
+            EngineKind::Threaded => self.strategy.to_string(),
+            EngineKind::Async => self.engine.to_string(),
+        };
+
+        match self.workload.label() {
+            workload if workload.is_empty() => engine,
+            workload => format!("{}/{}", engine, workload),
+        }
+    }
+
+    pub fn label(&self) -> String {
+        match self.closed_loop {
+            Some(ref report) => format!("{}/{}u", self.engine_label(), report.users),
+            None => self.engine_label(),
+        }
+    }
+
+    pub fn rows_per_sec(&self) -> f64 {
+        per_sec(self.metrics.rows_written, self.stats.total_duration)
+    }
+
+    pub fn bytes_per_sec(&self) -> f64 {
+        per_sec(self.metrics.bytes_written, self.stats.total_duration)
+    }
+}
+
+fn per_sec(count: u64, span: Duration) -> f64 {
+    if span.is_zero() {
+        0.0
+    } else {
+        count as f64 / span.as_secs_f64()
+    }
+}
+
+#[inline]
+fn make_request(i: u64) -> WorkRequest {
+    WorkRequest::new(format!("req-{}", i))
+}
+
+#[inline]
+fn make_warmup_request(i: u64) -> WorkRequest {
+    WorkRequest::new(format!("warmup-{}", i))
+}
+
+// Polls until every tracked request has a response or the timeout passes; returns
+// how many are still outstanding. Once cancelled, at most the grace period is left.
+fn wait_for_responses(tracker: &ResponseTracker, options: &RunOptions) -> u64 {
+    let mut deadline = std::time::Instant::now() + options.drain_timeout;
+    let mut graced = false;
+
+    while tracker.outstanding() > 0 && std::time::Instant::now() < deadline {
+        if !graced && options.cancel.is_cancelled() {
+            deadline = deadline.min(std::time::Instant::now() + options.shutdown_grace);
+            graced = true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    tracker.outstanding()
+}
+
+/// Sends warmup traffic through the already running workers, then resets the
+/// collectors so the main run starts from a clean slate.
+fn run_warmup(
+    tx: &crossbeam_channel::Sender<WorkEnvelope>,
+    options: &RunOptions,
+    metrics: &MetricsCollector,
+    benchmark: &BenchmarkCollector,
+    pool_metrics: &PoolMetricsCollector,
+) -> BenchmarkStats {
+    let warmup = &options.warmup;
+    let rps = warmup.rps.max(1);
+    let total_requests = if warmup.requests > 0 {
+        warmup.requests
+    } else {
+        u64::MAX
+    };
+    let pattern = if warmup.duration_secs > 0 {
+        LoadPattern::Burst {
+            rps,
+            duration_secs: warmup.duration_secs,
+        }
+    } else {
+        LoadPattern::Constant { rps }
+    };
+
+    println!("🔥 Warming up: {:?}", pattern);
+
+    let response_collector = ResponseCollector::start(warmup.requests);
+    let tracker = response_collector.tracker();
+    let load_handle = spawn_load_generator(
+        pattern,
+        total_requests,
+        tx.clone(),
+        response_collector.tracker(),
+        options.generator_options(),
+        options.cancel.clone(),
+        make_warmup_request,
+    );
+    let _ = load_handle.join();
+
+    let outstanding = wait_for_responses(&tracker, options);
+    if outstanding > 0 {
+        eprintln!("⚠️  {} warmup requests still in flight", outstanding);
+    }
+
+    let stats = benchmark.get_stats();
+    benchmark.reset();
+    metrics.reset();
+    pool_metrics.reset();
+
+    stats
+}
+
+/// Runs one load pattern through a fresh worker pool and collects its results.
+pub fn run_benchmark(
+    connections: ConnectionSource,
+    options: &RunOptions,
+) -> Result<RunResult, EngineError> {
+    let strategy = connections.strategy;
+
+    let queue_capacity = options.worker_count * 100;
+    let (tx, rx) = bounded::<WorkEnvelope>(queue_capacity);
+    // Kept so requests still queued after the drain timeout can be discarded
+    let drain_rx = rx.clone();
+
+    let metrics = MetricsCollector::new();
+    let benchmark = BenchmarkCollector::new();
+    let pool_metrics = PoolMetricsCollector::new();
+    let pool_sampler = spawn_pool_sampler(
+        connections.pool.clone(),
+        options.pool_sample_interval,
+        pool_metrics.clone_handle(),
+    );
+
+    let handles = match options.engine.kind {
+        EngineKind::Threaded => {
+            let handles = spawn_worker_pool_with_metrics(
+                options.worker_count,
+                connections,
+                rx,
+                metrics.clone_handle(),
+                Some(benchmark.clone_handle()),
+                Some(pool_metrics.clone_handle()),
+                options.worker_options(),
+            );
+
+            println!(
+                "✅ Worker pool started with {} workers ({} connections)\n",
+                options.worker_count, strategy
+            );
+
+            handles
+        }
+        EngineKind::Async => {
+            let handle = spawn_async_engine(
+                &connections,
+                &options.engine,
+                rx,
+                metrics.clone_handle(),
+                Some(benchmark.clone_handle()),
+            );
+
+            let handle = match handle {
+                Ok(handle) => handle,
+                Err(e) => {
+                    pool_sampler.stop();
+                    return Err(e);
+                }
+            };
+
+            println!(
+                "✅ Async engine started with {} connections on {} threads (max {} in flight)\n",
+                options.engine.connections,
+                options.engine.worker_threads,
+                options.engine.max_in_flight
+            );
+
+            vec![handle]
+        }
+    };
+
+    let warmup = options
+        .warmup
+        .is_enabled()
+        .then(|| run_warmup(&tx, options, &metrics, &benchmark, &pool_metrics));
+
+    let (generation_time, pacing, closed_loop, response_collector) = match options.mode {
+        LoadMode::Open(ref pattern) => {
+            let response_collector = ResponseCollector::start(options.total_requests);
+            let load_handle = spawn_load_generator(
+                pattern.clone(),
+                options.total_requests,
+                tx.clone(),
+                response_collector.tracker(),
+                options.generator_options(),
+                options.cancel.clone(),
+                make_request,
+            );
+
+            // Wait for load generation to complete
+            let pacing = load_handle.join().expect("Load generator panicked");
+            (pacing.elapsed, Some(pacing), None, Some(response_collector))
+        }
+        LoadMode::Closed { users, think_time } => {
+            let start = std::time::Instant::now();
+            let report = run_virtual_users(
+                users,
+                options.total_requests,
+                think_time,
+                tx.clone(),
+                options.cancel.clone(),
+                make_request,
+            );
+            (start.elapsed(), None, Some(report), None)
+        }
+    };
+
+    println!(
+        "⏱️  Load generation completed in {:.2}s\n",
+        generation_time.as_secs_f64()
+    );
+
+    // Closed-loop users already waited for every response they sent
+    if let Some(ref collector) = response_collector {
+        let outstanding = wait_for_responses(&collector.tracker(), options);
+        if outstanding > 0 {
+            eprintln!(
+                "⚠️  Drain timeout: {} requests still outstanding after {:.1}s",
+                outstanding,
+                options.drain_timeout.as_secs_f64()
+            );
+        }
+    }
+
+    // Stop workers from picking up anything still queued, then let them finish
+    drop(tx);
+    let abandoned = drain_rx.try_iter().count() as u64;
+    drop(drain_rx);
+    for handle in handles {
+        let _ = handle.join();
+    }
+    pool_sampler.stop();
+
+    // Only after the workers are gone is an unanswered request really lost
+    let responses = response_collector.map(ResponseCollector::finish);
+    let spike = match (&options.mode, &responses) {
+        (
+            LoadMode::Open(LoadPattern::Spike {
+                spike_at_secs,
+                spike_secs,
+                ..
+            }),
+            Some(report),
+        ) => analyze_spike(
+            &report.timeline,
+            Duration::from_secs(*spike_at_secs),
+            Duration::from_secs(spike_at_secs + spike_secs),
+        ),
+        _ => None,
+    };
+
+    Ok(RunResult {
+        engine: options.engine.kind,
+        strategy,
+        workload: options.workload,
+        generation_time,
+        pacing,
+        metrics: metrics.get_snapshot(),
+        stats: benchmark.get_stats(),
+        pool_stats: pool_metrics.get_stats(),
+        closed_loop,
+        responses,
+        spike,
+        warmup,
+        abandoned,
+        partial: options.cancel.is_cancelled(),
+    })
+}
+
+pub fn print_run_comparison(results: &[RunResult]) {
+    println!("\n{}", "=".repeat(114));
+    println!("RUN COMPARISON");
+    println!("{}", "=".repeat(114));
+
+    println!(
+        "\n  {:<26} {:>10} {:>11} {:>9} {:>8} {:>10} {:>10} {:>10} {:>12}",
+        "Run", "Req/s", "Rows/s", "MB/s", "Failed", "Avg ms", "P99 ms", "Max ms", "Acquire ms"
+    );
+
+    for result in results {
+        println!(
+            "  {:<26} {:>10.2} {:>11.0} {:>9.2} {:>8} {:>10.3} {:>10.3} {:>10.3} {:>12.3}{}",
+            result.label(),
+            result.stats.throughput_rps,
+            result.rows_per_sec(),
+            result.bytes_per_sec() / 1_048_576.0,
+            result.stats.failed_requests,
+            result.stats.avg_latency.as_secs_f64() * 1000.0,
+            result.stats.p99_latency.as_secs_f64() * 1000.0,
+            result.stats.max_latency.as_secs_f64() * 1000.0,
+            result.pool_stats.avg_checkout_wait.as_secs_f64() * 1000.0,
+            if result.partial { "  (partial)" } else { "" },
+        );
+    }
+
+    println!("\n{}", "=".repeat(114));
+}
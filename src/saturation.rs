@@ -0,0 +1,264 @@
+use serde::Deserialize;
+use std::time::Duration;
+
+use crate::error::EngineError;
+use crate::load_gen::LoadPattern;
+use crate::pool::ConnectionSource;
+use crate::runner::{run_benchmark, LoadMode, RunOptions, RunResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SearchStrategy {
+    // Increase by `step_rps` until a step fails
+    Linear,
+    // Double until a step fails, then bisect down to `step_rps` resolution
+    Binary,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SaturationSettings {
+    pub enabled: bool,
+    pub search: SearchStrategy,
+    pub start_rps: u64,
+    pub step_rps: u64,
+    pub max_rps: u64,
+    pub step_duration_secs: u64,
+    pub p99_slo_ms: f64,
+    pub max_error_rate: f64,
+    // A step also fails if it completes less than this fraction of its target rate
+    pub min_throughput_ratio: f64,
+}
+
+impl Default for SaturationSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            search: SearchStrategy::Linear,
+            start_rps: 100,
+            step_rps: 100,
+            max_rps: 10_000,
+            step_duration_secs: 10,
+            p99_slo_ms: 50.0,
+            max_error_rate: 0.01,
+            min_throughput_ratio: 0.9,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SaturationStep {
+    pub target_rps: u64,
+    pub achieved_rps: f64,
+    pub p50_latency: Duration,
+    pub p99_latency: Duration,
+    pub error_rate: f64,
+    // Why the step failed, if it did
+    pub failure: Option<String>,
+}
+
+impl SaturationStep {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct SaturationReport {
+    pub label: String,
+    pub steps: Vec<SaturationStep>,
+    pub max_sustainable_rps: Option<u64>,
+    // The search was interrupted before it could converge
+    pub partial: bool,
+}
+
+/// Runs successive constant-rate steps to find the highest rate that meets the SLO.
+pub fn run_saturation_search(
+    connections: &ConnectionSource,
+    base: &RunOptions,
+    settings: &SaturationSettings,
+) -> Result<SaturationReport, EngineError> {
+    let step_rps = settings.step_rps.max(1);
+    let mut steps = Vec::new();
+    let mut label = String::new();
+
+    let mut run_step = |rps: u64| -> Result<Option<bool>, EngineError> {
+        println!("🔎 Saturation step: {} rps", rps);
+
+        let options = RunOptions {
+            total_requests: rps * settings.step_duration_secs.max(1),
+            mode: LoadMode::Open(LoadPattern::Constant { rps }),
+            engine: base.engine.clone(),
+            cancel: base.cancel.clone(),
+            ..*base
+        };
+        let result = run_benchmark(connections.clone(), &options)?;
+        let step = evaluate_step(rps, &result, settings);
+
+        println!(
+            "   {} rps -> p99 {:.3} ms, errors {:.2}% ({})\n",
+            rps,
+            step.p99_latency.as_secs_f64() * 1000.0,
+            step.error_rate * 100.0,
+            step.failure.as_deref().unwrap_or("pass")
+        );
+
+        label = result.engine_label();
+        // An interrupted step proves nothing either way; stop searching
+        if result.partial {
+            return Ok(None);
+        }
+
+        let passed = step.passed();
+        steps.push(step);
+        Ok(Some(passed))
+    };
+
+    let mut best = None;
+    let mut rps = settings.start_rps.max(1);
+
+    // Climb until the first failure or the ceiling; binary search doubles to bracket the
+    // limit quickly, then bisects below
+    let mut first_failure = None;
+    let mut interrupted = false;
+    while rps <= settings.max_rps {
+        match run_step(rps)? {
+            Some(true) => best = Some(rps),
+            Some(false) => {
+                first_failure = Some(rps);
+                break;
+            }
+            None => {
+                interrupted = true;
+                break;
+            }
+        }
+
+        let next = match settings.search {
+            SearchStrategy::Linear => rps + step_rps,
+            SearchStrategy::Binary => (rps * 2).max(rps + step_rps),
+        };
+        if rps == settings.max_rps {
+            break;
+        }
+        rps = next.min(settings.max_rps);
+    }
+
+    if settings.search == SearchStrategy::Binary {
+        if let (Some(mut low), Some(mut high)) = (best, first_failure) {
+            while high - low > step_rps {
+                let mid = low + (high - low) / 2;
+                match run_step(mid)? {
+                    Some(true) => low = mid,
+                    Some(false) => high = mid,
+                    None => {
+                        interrupted = true;
+                        break;
+                    }
+                }
+            }
+            best = Some(low);
+        }
+    }
+
+    steps.sort_by_key(|step| step.target_rps);
+
+    Ok(SaturationReport {
+        label,
+        steps,
+        max_sustainable_rps: best,
+        partial: interrupted,
+    })
+}
+
+fn evaluate_step(
+    target_rps: u64,
+    result: &RunResult,
+    settings: &SaturationSettings,
+) -> SaturationStep {
+    // Caller-side round trip is the latency the SLO is about; fall back to service time
+    // Lost responses and requests shed under overload count as errors
+    let (latency, completed, lost) = match result.responses {
+        Some(ref responses) => (&responses.round_trip, responses.matched, responses.lost),
+        None => (&result.stats, result.stats.total_requests, 0),
+    };
+    let shed = result.pacing.as_ref().map_or(0, |pacing| pacing.shed);
+
+    let attempted = completed + lost + shed;
+    let failed = latency.failed_requests + lost + shed;
+    let error_rate = if attempted > 0 {
+        failed as f64 / attempted as f64
+    } else {
+        1.0
+    };
+
+    let achieved_rps = if result.generation_time.as_secs_f64() > 0.0 {
+        completed as f64 / result.generation_time.as_secs_f64()
+    } else {
+        0.0
+    };
+
+    let p99_ms = latency.p99_latency.as_secs_f64() * 1000.0;
+    let failure = if p99_ms > settings.p99_slo_ms {
+        Some(format!("p99 {:.3} ms > {} ms", p99_ms, settings.p99_slo_ms))
+    } else if error_rate > settings.max_error_rate {
+        Some(format!(
+            "error rate {:.2}% > {:.2}%",
+            error_rate * 100.0,
+            settings.max_error_rate * 100.0
+        ))
+    } else if achieved_rps < target_rps as f64 * settings.min_throughput_ratio {
+        Some(format!("achieved {:.1} rps", achieved_rps))
+    } else {
+        None
+    };
+
+    SaturationStep {
+        target_rps,
+        achieved_rps,
+        p50_latency: latency.p50_latency,
+        p99_latency: latency.p99_latency,
+        error_rate,
+        failure,
+    }
+}
+
+pub fn print_saturation_report(report: &SaturationReport, settings: &SaturationSettings) {
+    println!("\n{}", "=".repeat(78));
+    println!(
+        "SATURATION SEARCH ({}){}",
+        report.label,
+        if report.partial { " - PARTIAL" } else { "" }
+    );
+    println!("{}", "=".repeat(78));
+    println!(
+        "\n  SLO: p99 <= {} ms, errors <= {:.2}%, throughput >= {:.0}% of target",
+        settings.p99_slo_ms,
+        settings.max_error_rate * 100.0,
+        settings.min_throughput_ratio * 100.0
+    );
+
+    println!(
+        "\n  {:>10} {:>12} {:>10} {:>10} {:>8}  Result",
+        "Target", "Achieved", "P50 ms", "P99 ms", "Errors"
+    );
+
+    for step in &report.steps {
+        println!(
+            "  {:>10} {:>12.1} {:>10.3} {:>10.3} {:>7.2}%  {}",
+            step.target_rps,
+            step.achieved_rps,
+            step.p50_latency.as_secs_f64() * 1000.0,
+            step.p99_latency.as_secs_f64() * 1000.0,
+            step.error_rate * 100.0,
+            step.failure.as_deref().unwrap_or("pass")
+        );
+    }
+
+    match report.max_sustainable_rps {
+        Some(rps) => println!("\n  🏁 Max sustainable rate: {} rps", rps),
+        None => println!("\n  🏁 No step met the SLO"),
+    }
+
+    println!("\n{}", "=".repeat(78));
+}
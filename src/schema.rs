@@ -0,0 +1,229 @@
+use diesel::connection::SimpleConnection;
+use diesel::prelude::*;
+use serde::Deserialize;
+use std::fmt;
+
+use crate::dataset::quote_qualified;
+use crate::error::SchemaError;
+use crate::pool::DbPool;
+use crate::settings::PoolSettings;
+
+const RUN_SCHEMA_PREFIX: &str = "supafast_run_";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SchemaMode {
+    // Set up once before the first run and keep the data across runs
+    Reuse,
+    // Tear down and set up again before every run after the first
+    Recreate,
+    // Empty the `truncate` tables before every run after the first
+    Truncate,
+}
+
+impl fmt::Display for SchemaMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaMode::Reuse => f.write_str("reuse"),
+            SchemaMode::Recreate => f.write_str("recreate"),
+            SchemaMode::Truncate => f.write_str("truncate"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SchemaSettings {
+    pub mode: SchemaMode,
+    // Run in order before the first run; each entry may hold several statements
+    pub setup: Vec<String>,
+    // Run in order after the last run
+    pub teardown: Vec<String>,
+    // Tables emptied between runs in `truncate` mode, optionally schema-qualified
+    pub truncate: Vec<String>,
+    // Run everything in a fresh `supafast_run_<id>` schema, dropped when the run ends; not
+    // applied to `supafast seed`
+    pub isolate: bool,
+    // Leave the isolated schema in place for inspection
+    pub keep_schema: bool,
+}
+
+impl Default for SchemaSettings {
+    fn default() -> Self {
+        Self {
+            mode: SchemaMode::Reuse,
+            setup: vec![
+                "CREATE TABLE IF NOT EXISTS your_table (id text, created_at timestamptz)"
+                    .to_string(),
+            ],
+            teardown: Vec::new(),
+            truncate: vec!["your_table".to_string()],
+            isolate: false,
+            keep_schema: false,
+        }
+    }
+}
+
+/// Runs a scenario's setup and teardown SQL around the benchmark runs; once set up, it
+/// also tears down when dropped, e.g. after a failed run.
+pub struct SchemaManager {
+    pool: DbPool,
+    settings: SchemaSettings,
+    truncate: Option<String>,
+    phases: u64,
+    set_up: bool,
+}
+
+impl SchemaManager {
+    pub fn new(pool: DbPool, settings: &SchemaSettings) -> Result<Self, SchemaError> {
+        let tables = settings
+            .truncate
+            .iter()
+            .map(|table| {
+                quote_qualified(table).ok_or_else(|| SchemaError::InvalidTable(table.clone()))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            pool,
+            settings: settings.clone(),
+            truncate: (!tables.is_empty()).then(|| format!("TRUNCATE {}", tables.join(", "))),
+            phases: 0,
+            set_up: false,
+        })
+    }
+
+    pub fn setup(&mut self) -> Result<(), SchemaError> {
+        // Set first, so a setup that fails part way is still torn down
+        self.set_up = true;
+
+        if !self.settings.setup.is_empty() {
+            println!(
+                "🏗️  Schema setup: {} statements ({})",
+                self.settings.setup.len(),
+                self.settings.mode
+            );
+        }
+
+        self.execute(&self.settings.setup)
+    }
+
+    /// Call before each phase; prepares the schema for every phase after the first.
+    pub fn begin_phase(&mut self) -> Result<(), SchemaError> {
+        self.phases += 1;
+        if self.phases == 1 {
+            return Ok(());
+        }
+
+        match self.settings.mode {
+            SchemaMode::Reuse => Ok(()),
+            SchemaMode::Recreate => {
+                println!("🏗️  Recreating schema");
+                self.execute(&self.settings.teardown)?;
+                self.execute(&self.settings.setup)
+            }
+            SchemaMode::Truncate => match self.truncate {
+                Some(ref truncate) => self.execute(std::slice::from_ref(truncate)),
+                None => Ok(()),
+            },
+        }
+    }
+
+    pub fn teardown(&mut self) -> Result<(), SchemaError> {
+        self.set_up = false;
+        if !self.settings.teardown.is_empty() {
+            println!(
+                "🧹 Schema teardown: {} statements",
+                self.settings.teardown.len()
+            );
+        }
+
+        self.execute(&self.settings.teardown)
+    }
+
+    // Simple-protocol batches, so an entry can hold DDL that cannot be prepared
+    fn execute(&self, statements: &[String]) -> Result<(), SchemaError> {
+        if statements.is_empty() {
+            return Ok(());
+        }
+
+        let mut conn = self.pool.get()?;
+        for statement in statements {
+            conn.batch_execute(statement)
+                .map_err(|source| SchemaError::Statement {
+                    statement: statement.clone(),
+                    source,
+                })?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Drop for SchemaManager {
+    fn drop(&mut self) {
+        if !self.set_up {
+            return;
+        }
+
+        if let Err(e) = self.teardown() {
+            eprintln!("⚠️  Schema teardown failed: {}", e);
+        }
+    }
+}
+
+/// A schema of its own for one run, dropped with everything in it when this value goes
+/// out of scope unless `keep` is set.
+pub struct RunSchema {
+    name: String,
+    database_url: String,
+    keep: bool,
+}
+
+impl RunSchema {
+    pub fn create(database_url: &str, keep: bool) -> Result<Self, SchemaError> {
+        let name = format!("{}{:08x}", RUN_SCHEMA_PREFIX, rand::random::<u32>());
+
+        let mut conn = PgConnection::establish(database_url)?;
+        conn.batch_execute(&format!("CREATE SCHEMA \"{}\"", name))?;
+
+        Ok(Self {
+            name,
+            database_url: database_url.to_string(),
+            keep,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Points every connection made with `settings` at this schema. `public` stays on the
+    /// path so extensions installed there remain usable.
+    pub fn apply(&self, settings: &mut PoolSettings) {
+        settings
+            .init_sql
+            .insert(0, format!("SET search_path TO \"{}\", public", self.name));
+    }
+}
+
+impl Drop for RunSchema {
+    fn drop(&mut self) {
+        if self.keep {
+            println!("📌 Keeping schema {}", self.name);
+            return;
+        }
+
+        let result = PgConnection::establish(&self.database_url)
+            .map_err(SchemaError::from)
+            .and_then(|mut conn| {
+                conn.batch_execute(&format!("DROP SCHEMA \"{}\" CASCADE", self.name))
+                    .map_err(SchemaError::from)
+            });
+
+        match result {
+            Ok(()) => println!("🧹 Dropped schema {}", self.name),
+            Err(e) => eprintln!("⚠️  Failed to drop schema {}: {}", self.name, e),
+        }
+    }
+}
@@ -0,0 +1,61 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RngStream {
+    Generator = 1,
+    Worker = 2,
+    // One stream per `supafast seed` batch
+    Dataset = 3,
+}
+
+/// The one seed every random choice in a run is derived from, one ChaCha stream per
+/// generator and worker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RunSeed(u64);
+
+impl RunSeed {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Uses the configured seed, or picks a fresh one to print for reruns.
+    pub fn from_config(seed: Option<u64>) -> Self {
+        // TOML integers are signed, so a picked seed must fit in an i64 to be written back
+        Self(seed.unwrap_or_else(|| rand::random_range(0..=i64::MAX as u64)))
+    }
+
+    pub fn value(self) -> u64 {
+        self.0
+    }
+
+    /// Deterministic RNG for instance `index` of a component.
+    pub fn rng(self, stream: RngStream, index: u64) -> ChaCha8Rng {
+        let mut rng = ChaCha8Rng::seed_from_u64(self.0);
+        rng.set_stream(((stream as u64) << 48) | index);
+        rng
+    }
+}
+
+impl fmt::Display for RunSeed {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::settings::Settings;
+
+    #[test]
+    fn picked_seeds_parse_back_from_config() {
+        for _ in 0..1000 {
+            let seed = RunSeed::from_config(None);
+            let settings = Settings::from_toml(&format!("[run]\nseed = {}", seed)).unwrap();
+
+            assert_eq!(RunSeed::from_config(settings.run.seed), seed);
+        }
+    }
+}
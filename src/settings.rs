@@ -0,0 +1,204 @@
+use serde::Deserialize;
+use std::path::Path;
+
+use crate::arrival::{ArrivalProcess, ArrivalSettings};
+use crate::curve::CurveSettings;
+use crate::dataset::DatasetSettings;
+use crate::error::ConfigError;
+use crate::load_gen::BackpressureSettings;
+use crate::pool::ConnectionStrategy;
+use crate::replay::ReplaySettings;
+use crate::runner::EngineKind;
+use crate::saturation::SaturationSettings;
+use crate::schema::SchemaSettings;
+use crate::workload::WorkloadSettings;
+
+// Environment variable pointing at an optional TOML config file
+pub const CONFIG_ENV_VAR: &str = "SUPAFAST_CONFIG";
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Settings {
+    pub run: RunSettings,
+    pub pool: PoolSettings,
+    pub worker: WorkerSettings,
+    pub engine: EngineSettings,
+    pub closed_loop: ClosedLoopSettings,
+    pub saturation: SaturationSettings,
+    pub warmup: WarmupSettings,
+    pub arrival: ArrivalSettings,
+    pub replay: ReplaySettings,
+    pub curve: CurveSettings,
+    pub backpressure: BackpressureSettings,
+    // Table and column generators for `supafast seed`
+    pub dataset: DatasetSettings,
+    // Scenario setup/teardown SQL around the benchmark runs
+    pub schema: SchemaSettings,
+    pub workload: WorkloadSettings,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RunSettings {
+    // After load generation, how long to wait for in-flight requests before abandoning them
+    pub drain_timeout_secs: u64,
+    // Once interrupted (Ctrl-C/SIGTERM), the drain is cut short to at most this long
+    pub shutdown_grace_secs: u64,
+    // Open-loop load generator threads; raise for rates one thread cannot send
+    pub generators: usize,
+    // Run seed every RNG stream is derived from; a random one is picked when unset
+    pub seed: Option<u64>,
+}
+
+impl Default for RunSettings {
+    fn default() -> Self {
+        Self {
+            drain_timeout_secs: 30,
+            shutdown_grace_secs: 5,
+            generators: 1,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PoolSettings {
+    // Applied in order with `set_config(name, value, false)` on every new connection, so
+    // values are parsed as `SET` would, e.g. `search_path = "app, public"`
+    pub session_settings: Vec<SessionSetting>,
+    // Arbitrary SQL run after the session settings
+    pub init_sql: Vec<String>,
+    // How often the pool state (connections/idle) is sampled for the report
+    pub sample_interval_ms: u64,
+}
+
+impl Default for PoolSettings {
+    fn default() -> Self {
+        Self {
+            session_settings: Vec::new(),
+            init_sql: Vec::new(),
+            sample_interval_ms: 250,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkerSettings {
+    pub connection_strategies: Vec<ConnectionStrategy>,
+}
+
+impl Default for WorkerSettings {
+    fn default() -> Self {
+        Self {
+            connection_strategies: vec![ConnectionStrategy::Cached],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct EngineSettings {
+    pub kind: EngineKind,
+    // The fields below only apply to the async engine
+    pub worker_threads: usize,
+    pub connections: usize,
+    pub max_in_flight: usize,
+}
+
+impl Default for EngineSettings {
+    fn default() -> Self {
+        Self {
+            kind: EngineKind::Threaded,
+            worker_threads: 2,
+            connections: 4,
+            max_in_flight: 1024,
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClosedLoopSettings {
+    // Virtual user counts to sweep; empty keeps the open-loop load pattern
+    pub users: Vec<usize>,
+    pub think_time_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WarmupSettings {
+    // Warmup ends after this many requests or this many seconds, whichever comes first;
+    // zero disables that limit, both zero disables warmup
+    pub requests: u64,
+    pub duration_secs: u64,
+    pub rps: u64,
+}
+
+impl WarmupSettings {
+    pub fn is_enabled(&self) -> bool {
+        self.requests > 0 || self.duration_secs > 0
+    }
+}
+
+impl Default for WarmupSettings {
+    fn default() -> Self {
+        Self {
+            requests: 0,
+            duration_secs: 0,
+            rps: 100,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionSetting {
+    pub name: String,
+    pub value: String,
+}
+
+impl Settings {
+    /// Loads settings from the file named by `SUPAFAST_CONFIG`, or defaults if unset.
+    pub fn load() -> Result<Self, ConfigError> {
+        match std::env::var(CONFIG_ENV_VAR) {
+            Ok(path) => Self::from_file(path),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let contents = std::fs::read_to_string(path)?;
+        Self::from_toml(&contents)
+    }
+
+    pub fn from_toml(contents: &str) -> Result<Self, ConfigError> {
+        let settings: Self = toml::from_str(contents)?;
+        settings.validate()?;
+        Ok(settings)
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        // Lists that multiply into runs; an empty one would run nothing without saying so
+        if self.worker.connection_strategies.is_empty() {
+            return Err(ConfigError::Invalid(
+                "[worker] connection_strategies must list at least one strategy",
+            ));
+        }
+        if self.workload.kinds.is_empty() {
+            return Err(ConfigError::Invalid(
+                "[workload] kinds must list at least one workload",
+            ));
+        }
+        // Curves space their sends in requests, not seconds, so on/off periods measured
+        // in milliseconds would not mean anything there
+        if !self.curve.points.is_empty() && self.arrival.process == ArrivalProcess::OnOff {
+            return Err(ConfigError::Invalid(
+                "[arrival] process = \"on_off\" cannot be combined with a [curve]",
+            ));
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Long sleeps are taken in slices this long so cancellation is noticed promptly
+const CANCEL_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Shared flag telling load generators and drivers to stop early.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Sleeps until `due`; returns false as soon as the token is cancelled instead.
+    pub fn sleep_until(&self, due: Instant) -> bool {
+        loop {
+            if self.is_cancelled() {
+                return false;
+            }
+            let remaining = due.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return true;
+            }
+            thread::sleep(remaining.min(CANCEL_CHECK_INTERVAL));
+        }
+    }
+}
+
+/// Cancels `token` on the first SIGINT/SIGTERM and exits immediately on the second.
+pub fn install_signal_handler(token: CancellationToken) -> Result<(), ctrlc::Error> {
+    let signals = AtomicUsize::new(0);
+
+    ctrlc::set_handler(move || {
+        if signals.fetch_add(1, Ordering::SeqCst) == 0 {
+            eprintln!("\n🛑 Interrupted: stopping load and draining (signal again to force exit)");
+            token.cancel();
+        } else {
+            eprintln!("\n🛑 Forced exit");
+            std::process::exit(130);
+        }
+    })
+}
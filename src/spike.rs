@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::benchmark::latency_percentile;
+use crate::responses::LatencySample;
+
+// Latency is judged per window of dispatch time
+const WINDOW: Duration = Duration::from_millis(500);
+// A window is back at baseline when its p99 is within this factor of the pre-spike p99
+const RECOVERY_TOLERANCE: f64 = 1.25;
+// ...and it has to stay there for this many consecutive windows, so one quiet window
+// mid-backlog does not count as recovered
+const SETTLED_WINDOWS: usize = 3;
+
+#[derive(Debug, Clone)]
+pub struct SpikeRecovery {
+    pub spike_start: Duration,
+    pub spike_end: Duration,
+    // p99 of everything dispatched before the spike
+    pub baseline_p99: Duration,
+    // Worst window p99 from the spike onwards
+    pub peak_p99: Duration,
+    // From the end of the spike until latency returned to baseline and settled there;
+    // None if it never did before the run ended
+    pub recovery: Option<Duration>,
+}
+
+/// Measures how long latency takes to settle back to its pre-spike level; None without
+/// samples on both sides of the spike.
+pub fn analyze_spike(
+    timeline: &[LatencySample],
+    spike_start: Duration,
+    spike_end: Duration,
+) -> Option<SpikeRecovery> {
+    let mut baseline: Vec<Duration> = timeline
+        .iter()
+        .filter(|sample| sample.dispatched < spike_start)
+        .map(|sample| sample.latency)
+        .collect();
+    if baseline.is_empty() {
+        return None;
+    }
+    baseline.sort();
+    let baseline_p99 = latency_percentile(&baseline, 0.99);
+
+    // Windows are aligned to the spike start so one of them begins exactly at its end
+    let mut windows: BTreeMap<u64, Vec<Duration>> = BTreeMap::new();
+    for sample in timeline.iter().filter(|s| s.dispatched >= spike_start) {
+        let index = (sample.dispatched - spike_start).as_nanos() / WINDOW.as_nanos();
+        windows
+            .entry(index as u64)
+            .or_default()
+            .push(sample.latency);
+    }
+
+    let window_p99: Vec<(Duration, Duration)> = windows
+        .into_iter()
+        .map(|(index, mut latencies)| {
+            latencies.sort();
+            (
+                spike_start + WINDOW * index as u32,
+                latency_percentile(&latencies, 0.99),
+            )
+        })
+        .collect();
+
+    let peak_p99 = window_p99
+        .iter()
+        .map(|&(_, p99)| p99)
+        .max()
+        .unwrap_or_default();
+
+    let after: Vec<_> = window_p99
+        .iter()
+        .filter(|&&(start, _)| start >= spike_end)
+        .collect();
+    if after.is_empty() {
+        return None;
+    }
+
+    // Near the end of the run fewer windows are left; all of them must then be settled
+    let threshold = baseline_p99.mul_f64(RECOVERY_TOLERANCE);
+    let recovery = (0..after.len())
+        .find(|&i| {
+            after[i..]
+                .iter()
+                .take(SETTLED_WINDOWS)
+                .all(|&&(_, p99)| p99 <= threshold)
+        })
+        .map(|i| after[i].0 - spike_end);
+
+    Some(SpikeRecovery {
+        spike_start,
+        spike_end,
+        baseline_p99,
+        peak_p99,
+        recovery,
+    })
+}
+
+pub fn print_spike_report(report: &SpikeRecovery) {
+    println!(
+        "\n📈 Spike ({:.1}s - {:.1}s):",
+        report.spike_start.as_secs_f64(),
+        report.spike_end.as_secs_f64()
+    );
+    println!(
+        "  Baseline P99:        {:>10.3} ms",
+        report.baseline_p99.as_secs_f64() * 1000.0
+    );
+    println!(
+        "  Peak P99:            {:>10.3} ms",
+        report.peak_p99.as_secs_f64() * 1000.0
+    );
+    match report.recovery {
+        Some(recovery) => println!("  Recovery Time:       {:>10.3} s", recovery.as_secs_f64()),
+        None => println!("  Recovery Time:       not recovered before the run ended"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPIKE_START: Duration = Duration::from_secs(2);
+    const SPIKE_END: Duration = Duration::from_secs(3);
+
+    // One sample every 50ms over `[from, to)` seconds, with the latency in ms at that time
+    fn timeline(from: f64, to: f64, latency_ms: impl Fn(f64) -> u64) -> Vec<LatencySample> {
+        (0..)
+            .map(|i| from + i as f64 * 0.05)
+            .take_while(|&t| t < to)
+            .map(|t| LatencySample {
+                dispatched: Duration::from_secs_f64(t),
+                latency: Duration::from_millis(latency_ms(t)),
+            })
+            .collect()
+    }
+
+    fn analyze(timeline: &[LatencySample]) -> Option<SpikeRecovery> {
+        analyze_spike(timeline, SPIKE_START, SPIKE_END)
+    }
+
+    #[test]
+    fn no_baseline_without_pre_spike_samples() {
+        let timeline = timeline(2.0, 8.0, |_| 10);
+
+        assert!(analyze(&timeline).is_none());
+    }
+
+    #[test]
+    fn latency_that_stays_high_never_recovers() {
+        let timeline = timeline(0.0, 8.0, |t| if t < 2.0 { 10 } else { 100 });
+        let report = analyze(&timeline).unwrap();
+
+        assert_eq!(report.baseline_p99, Duration::from_millis(10));
+        assert_eq!(report.peak_p99, Duration::from_millis(100));
+        assert_eq!(report.recovery, None);
+    }
+
+    #[test]
+    fn recovery_is_timed_from_the_spike_end() {
+        // The backlog takes two windows after the spike to clear
+        let timeline = timeline(0.0, 8.0, |t| if (2.0..4.0).contains(&t) { 100 } else { 10 });
+        let report = analyze(&timeline).unwrap();
+
+        assert_eq!(report.recovery, Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn one_quiet_window_mid_backlog_does_not_count() {
+        let timeline = timeline(0.0, 8.0, |t| match t {
+            t if t < 2.0 => 10,
+            t if (3.5..4.0).contains(&t) => 10,
+            t if t < 5.0 => 100,
+            _ => 10,
+        });
+        let report = analyze(&timeline).unwrap();
+
+        assert_eq!(report.recovery, Some(Duration::from_secs(2)));
+    }
+}
@@ -1,44 +1,125 @@
-use crossbeam_channel::{Receiver, Sender};
+use crossbeam_channel::{Receiver, RecvTimeoutError};
+use diesel::connection::SimpleConnection;
+use diesel::pg::Pg;
 use diesel::prelude::*;
+use diesel::query_builder::{AstPass, QueryFragment, QueryId};
+use diesel::result::{DatabaseErrorKind, Error as DieselError};
+use diesel::sql_types::{Array, Text};
+use rand_chacha::ChaCha8Rng;
+use std::io::Write;
 use std::thread;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use crate::benchmark::BenchmarkCollector;
 use crate::error::WorkerError;
 use crate::metrics::MetricsCollector;
-use crate::models::{ResponseStatus, WorkRequest, WorkResponse};
-use crate::pool::{DbConnection, DbPool};
+use crate::models::{ResponseStatus, WorkEnvelope, WorkRequest, WorkResponse};
+use crate::pool::{ConnectionSource, ConnectionStrategy, DbConnection};
+use crate::pool_metrics::PoolMetricsCollector;
+use crate::seed::{RngStream, RunSeed};
+use crate::workload::{QueryMode, Workload, WorkloadKind};
+
+// COPY needs a table type; like the INSERT, `your_table` resolves through search_path
+diesel::table! {
+    your_table (id) {
+        id -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+// A request taken off the queue, waiting for the rest of its batch
+struct Queued {
+    envelope: WorkEnvelope,
+    dequeued: Instant,
+}
+
+/// Settings every worker in a pool shares for one run.
+#[derive(Debug, Clone, Copy)]
+pub struct WorkerOptions {
+    // Each worker's RNG stream derives from this
+    pub seed: RunSeed,
+    pub workload: Workload,
+}
 
 pub struct Worker {
     worker_id: usize,
-    db_pool: DbPool,
-    work_queue: Receiver<(WorkRequest, Sender<WorkResponse>)>,
+    connections: ConnectionSource,
+    work_queue: Receiver<WorkEnvelope>,
     cached_connection: Option<DbConnection>,
+    connections_acquired: u64,
+    pool_metrics: Option<PoolMetricsCollector>,
+    workload: Workload,
+    rng: ChaCha8Rng,
 }
 
 impl Worker {
     pub fn new(
         worker_id: usize,
-        db_pool: DbPool,
-        queue: Receiver<(WorkRequest, Sender<WorkResponse>)>,
+        connections: ConnectionSource,
+        queue: Receiver<WorkEnvelope>,
+        pool_metrics: Option<PoolMetricsCollector>,
+        options: WorkerOptions,
     ) -> Self {
         Self {
             worker_id,
-            db_pool,
+            connections,
             work_queue: queue,
             cached_connection: None,
+            connections_acquired: 0,
+            pool_metrics,
+            workload: options.workload,
+            rng: options.seed.rng(RngStream::Worker, worker_id as u64),
         }
     }
 
+    /// This worker's own deterministic RNG stream, for randomized request handling.
+    /// The draws repeat per worker; which requests a worker serves is up to the queue.
+    pub fn rng(&mut self) -> &mut ChaCha8Rng {
+        &mut self.rng
+    }
+
     #[inline]
     fn get_connection(&mut self) -> Result<&mut DbConnection, WorkerError> {
         if self.cached_connection.is_none() {
-            self.cached_connection = Some(self.db_pool.get()?);
+            let conn = self.checkout_connection()?;
+
+            if self.connections_acquired > 0 {
+                if let Some(ref pool_metrics) = self.pool_metrics {
+                    pool_metrics.record_reconnect(self.worker_id);
+                }
+            }
+
+            self.cached_connection = Some(conn);
+            self.connections_acquired += 1;
         }
 
         Ok(self.cached_connection.as_mut().unwrap())
     }
 
+    #[inline]
+    fn checkout_connection(&self) -> Result<DbConnection, WorkerError> {
+        let start = Instant::now();
+        let result = self.connections.pool.get();
+
+        if let Some(ref pool_metrics) = self.pool_metrics {
+            pool_metrics.record_checkout(start.elapsed(), result.is_ok());
+        }
+
+        Ok(result?)
+    }
+
+    // Connection setup time is recorded as the checkout wait for this strategy
+    fn establish_connection(&self) -> Result<PgConnection, WorkerError> {
+        let start = Instant::now();
+        let result = self.connections.establish();
+
+        if let Some(ref pool_metrics) = self.pool_metrics {
+            pool_metrics.record_checkout(start.elapsed(), result.is_ok());
+        }
+
+        result
+    }
+
     #[inline(always)]
     fn validate_request(&self, req: &WorkRequest) -> Result<(), WorkerError> {
         if req.id.is_empty() {
@@ -48,18 +129,34 @@ impl Worker {
         Ok(())
     }
 
+    // Runs `f` on a connection obtained the way the strategy says
+    #[inline]
+    fn with_connection<T>(
+        &mut self,
+        f: impl FnOnce(&mut PgConnection) -> Result<T, WorkerError>,
+    ) -> Result<T, WorkerError> {
+        match self.connections.strategy {
+            ConnectionStrategy::Cached => f(self.get_connection()?),
+            ConnectionStrategy::PerRequest => f(&mut *self.checkout_connection()?),
+            ConnectionStrategy::Dedicated => f(&mut self.establish_connection()?),
+        }
+    }
+
     fn process_request_internal(
         &mut self,
         request: &WorkRequest,
     ) -> Result<WorkResponse, WorkerError> {
         self.validate_request(request)?;
-        let conn = self.get_connection()?;
 
-        diesel::sql_query("INSERT INTO your_table (id, created_at) VALUES ($1, NOW())")
-            .bind::<diesel::sql_types::Text, _>(&request.id)
-            .execute(conn)?;
-
-        Ok(WorkResponse::success(request.id.clone()))
+        // A replayed operation overrides the kind, keeping the rest of the run's workload
+        let workload = match request.operation {
+            Some(kind) => Workload {
+                kind,
+                ..self.workload
+            },
+            None => self.workload,
+        };
+        self.with_connection(|conn| execute(conn, request, workload))
     }
 
     #[inline]
@@ -67,21 +164,113 @@ impl Worker {
         match self.process_request_internal(request) {
             Ok(response) => response,
             Err(e) => {
-                if cfg!(debug_assertions) {
-                    eprintln!("Worker {} error: {}", self.worker_id, e);
+                let status = self.failure_status(e);
+                WorkResponse::failure(request.id.clone(), status)
+            }
+        }
+    }
+
+    // Runs the batch as one statement; invalid requests and replayed ones for another
+    // operation are answered on their own, and a failed statement fails all the rest
+    fn process_batch(&mut self, batch: &[Queued]) -> Vec<WorkResponse> {
+        let kind = self.workload.kind;
+        let batched = |request: &WorkRequest| request.operation.is_none_or(|op| op == kind);
+
+        let valid: Vec<&str> = batch
+            .iter()
+            .map(|queued| &queued.envelope.request)
+            .filter(|request| batched(request) && self.validate_request(request).is_ok())
+            .map(WorkRequest::row_id)
+            .collect();
+
+        let workload = self.workload;
+        let status = if valid.is_empty() {
+            None
+        } else {
+            self.with_connection(|conn| execute_insert(conn, valid, workload))
+                .err()
+                .map(|e| self.failure_status(e))
+        };
+
+        batch
+            .iter()
+            .map(|queued| {
+                let request = &queued.envelope.request;
+                if !batched(request) {
+                    return self.process_request(request);
+                }
+                if self.validate_request(request).is_err() {
+                    return WorkResponse::failure(request.id.clone(), ResponseStatus::Invalid);
                 }
 
-                let status = match e {
-                    WorkerError::ValidationError(_) => ResponseStatus::Invalid,
-                    WorkerError::ConnectionError(_) => {
-                        self.cached_connection = None;
-                        ResponseStatus::ConnectionError
-                    }
-                    WorkerError::DatabaseError(_) => ResponseStatus::Failed,
-                    WorkerError::ProcessingError => ResponseStatus::Failed,
-                };
+                match status {
+                    None => WorkResponse::success(request.id.clone())
+                        .written(1, request.row_id().len() as u64),
+                    Some(status) => WorkResponse::failure(request.id.clone(), status),
+                }
+            })
+            .collect()
+    }
 
-                WorkResponse::failure(request.id.clone(), status)
+    // Maps a failed request to its response status, dropping a connection that is gone
+    fn failure_status(&mut self, e: WorkerError) -> ResponseStatus {
+        if cfg!(debug_assertions) {
+            eprintln!("Worker {} error: {}", self.worker_id, e);
+        }
+
+        match e {
+            WorkerError::ValidationError(_) => ResponseStatus::Invalid,
+            WorkerError::ConnectionError(_) => {
+                self.cached_connection = None;
+                ResponseStatus::ConnectionError
+            }
+            WorkerError::EstablishError(_) => ResponseStatus::ConnectionError,
+            WorkerError::DatabaseError(DieselError::DatabaseError(
+                DatabaseErrorKind::ClosedConnection,
+                _,
+            )) => {
+                self.cached_connection = None;
+                ResponseStatus::ConnectionError
+            }
+            WorkerError::DatabaseError(_) => ResponseStatus::Failed,
+            WorkerError::ProcessingError => ResponseStatus::Failed,
+        }
+    }
+
+    // Blocks for the first request, then keeps taking more until the batch is full or
+    // `batch_wait` has passed since the first one; None once the queue is closed
+    fn next_batch(&self) -> Option<Vec<Queued>> {
+        let first = self.work_queue.recv().ok()?;
+        let deadline = Instant::now() + self.workload.batch_wait;
+
+        let mut batch = Vec::with_capacity(self.workload.batch_size);
+        batch.push(Queued {
+            envelope: first,
+            dequeued: Instant::now(),
+        });
+
+        while batch.len() < self.workload.batch_size {
+            match self.work_queue.recv_deadline(deadline) {
+                Ok(envelope) => batch.push(Queued {
+                    envelope,
+                    dequeued: Instant::now(),
+                }),
+                Err(RecvTimeoutError::Timeout) | Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+
+        Some(batch)
+    }
+
+    // Each request's latency runs from when it was taken off the queue, so time spent
+    // waiting for the batch to fill counts against it
+    fn run_batched(&mut self, mut on_complete: impl FnMut(Duration, &WorkResponse)) {
+        while let Some(batch) = self.next_batch() {
+            let responses = self.process_batch(&batch);
+
+            for (queued, response) in batch.into_iter().zip(responses) {
+                on_complete(queued.dequeued.elapsed(), &response);
+                queued.envelope.complete(response);
             }
         }
     }
@@ -89,15 +278,163 @@ impl Worker {
     pub fn run(&mut self) {
         println!("Worker {} started", self.worker_id);
 
-        while let Ok((request, response_tx)) = self.work_queue.recv() {
-            let result = self.process_request(&request);
-            let _ = response_tx.send(result);
+        if self.workload.is_batched() {
+            self.run_batched(|_, _| {});
+        } else {
+            while let Ok(envelope) = self.work_queue.recv() {
+                let result = self.process_request(&envelope.request);
+                envelope.complete(result);
+            }
         }
 
         println!("Worker {} shutting down", self.worker_id);
     }
 }
 
+#[inline]
+fn execute(
+    conn: &mut PgConnection,
+    request: &WorkRequest,
+    workload: Workload,
+) -> Result<WorkResponse, WorkerError> {
+    match workload.kind {
+        WorkloadKind::Copy => execute_copy(conn, request, workload.copy_rows),
+        // Batched kinds outside the batching loop insert a batch of one
+        WorkloadKind::Insert | WorkloadKind::MultiRow | WorkloadKind::Unnest => {
+            let row_id = request.row_id();
+            execute_insert(conn, vec![row_id], workload)?;
+            Ok(WorkResponse::success(request.id.clone()).written(1, row_id.len() as u64))
+        }
+    }
+}
+
+// One INSERT for all of `ids`, sent the way the workload's query mode says
+#[inline]
+fn execute_insert(
+    conn: &mut PgConnection,
+    ids: Vec<&str>,
+    workload: Workload,
+) -> Result<usize, WorkerError> {
+    let rows = ids.len();
+    let statement = InsertStatement {
+        ids,
+        unnest: workload.kind == WorkloadKind::Unnest,
+        prepared: workload.query_mode == QueryMode::Prepared,
+    };
+
+    let inserted = match workload.query_mode {
+        QueryMode::Prepared | QueryMode::Unnamed => statement.execute(conn)?,
+        QueryMode::Simple => {
+            conn.batch_execute(&statement.to_literal_sql())?;
+            rows
+        }
+    };
+
+    Ok(inserted)
+}
+
+// One VALUES row per id, or one text[] through UNNEST. Unlike `sql_query`, which is
+// never cached, this is prepared once per connection and SQL text when `prepared`
+struct InsertStatement<'a> {
+    ids: Vec<&'a str>,
+    unnest: bool,
+    prepared: bool,
+}
+
+impl InsertStatement<'_> {
+    // The same statement for the simple protocol, with every id inlined as a literal
+    fn to_literal_sql(&self) -> String {
+        let mut sql = String::with_capacity(64 + self.ids.len() * 24);
+
+        if self.unnest {
+            sql.push_str(
+                "INSERT INTO your_table (id, created_at) SELECT id, NOW() FROM UNNEST(ARRAY[",
+            );
+            for (i, id) in self.ids.iter().enumerate() {
+                if i > 0 {
+                    sql.push_str(", ");
+                }
+                push_literal(&mut sql, id);
+            }
+            sql.push_str("]::text[]) AS batch(id)");
+        } else {
+            sql.push_str("INSERT INTO your_table (id, created_at) VALUES ");
+            for (i, id) in self.ids.iter().enumerate() {
+                sql.push_str(if i > 0 { ", (" } else { "(" });
+                push_literal(&mut sql, id);
+                sql.push_str(", NOW())");
+            }
+        }
+
+        sql
+    }
+}
+
+impl QueryFragment<Pg> for InsertStatement<'_> {
+    fn walk_ast<'b>(&'b self, mut out: AstPass<'_, 'b, Pg>) -> QueryResult<()> {
+        if !self.prepared {
+            out.unsafe_to_cache_prepared();
+        }
+
+        if self.unnest {
+            out.push_sql("INSERT INTO your_table (id, created_at) SELECT id, NOW() FROM UNNEST(");
+            out.push_bind_param::<Array<Text>, _>(&self.ids)?;
+            out.push_sql(") AS batch(id)");
+        } else {
+            out.push_sql("INSERT INTO your_table (id, created_at) VALUES ");
+            for (i, id) in self.ids.iter().enumerate() {
+                out.push_sql(if i > 0 { ", (" } else { "(" });
+                out.push_bind_param::<Text, _>(*id)?;
+                out.push_sql(", NOW())");
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl QueryId for InsertStatement<'_> {
+    type QueryId = ();
+
+    const HAS_STATIC_QUERY_ID: bool = false;
+}
+
+impl RunQueryDsl<PgConnection> for InsertStatement<'_> {}
+
+// Quotes a value as a standard-conforming string literal
+fn push_literal(sql: &mut String, value: &str) {
+    sql.push('\'');
+    sql.push_str(&value.replace('\'', "''"));
+    sql.push('\'');
+}
+
+// Streams `rows` rows derived from the row id in COPY text format; `now` is
+// timestamptz input for the current transaction time, matching the INSERT's NOW()
+fn execute_copy(
+    conn: &mut PgConnection,
+    request: &WorkRequest,
+    rows: usize,
+) -> Result<WorkResponse, WorkerError> {
+    let row_id = request.row_id();
+    let mut data = Vec::with_capacity(rows * (row_id.len() + 12));
+    let mut id_bytes = 0;
+    for row in 0..rows {
+        let start = data.len();
+        write!(data, "{}-{}", row_id, row).expect("Vec write cannot fail");
+        id_bytes += data.len() - start;
+        data.extend_from_slice(b"\tnow\n");
+    }
+
+    let copied = diesel::copy_from(your_table::table)
+        .from_raw_data(your_table::table, |copy| {
+            copy.write_all(&data)
+                .map_err(|e| DieselError::SerializationError(Box::new(e)))
+        })
+        .execute(conn)?;
+
+    Ok(WorkResponse::success(request.id.clone()).written(copied as u64, id_bytes as u64))
+}
+
 pub struct WorkerWithMetrics {
     worker: Worker,
     metrics: MetricsCollector,
@@ -107,13 +444,15 @@ pub struct WorkerWithMetrics {
 impl WorkerWithMetrics {
     pub fn new(
         worker_id: usize,
-        db_pool: DbPool,
-        queue: Receiver<(WorkRequest, Sender<WorkResponse>)>,
+        connections: ConnectionSource,
+        queue: Receiver<WorkEnvelope>,
         metrics: MetricsCollector,
         benchmark: Option<BenchmarkCollector>,
+        pool_metrics: Option<PoolMetricsCollector>,
+        options: WorkerOptions,
     ) -> Self {
         Self {
-            worker: Worker::new(worker_id, db_pool, queue),
+            worker: Worker::new(worker_id, connections, queue, pool_metrics, options),
             metrics,
             benchmark,
         }
@@ -122,44 +461,66 @@ impl WorkerWithMetrics {
     pub fn run(&mut self) {
         println!("Worker {} started", self.worker.worker_id);
 
-        while let Ok((request, response_tx)) = self.worker.work_queue.recv() {
-            let start = Instant::now();
-            let result = self.worker.process_request(&request);
-            let latency = start.elapsed();
-
-            // Track metrics
-            if result.success {
-                self.metrics.record_success();
-            } else {
-                self.metrics.record_failure();
-            }
+        if self.worker.workload.is_batched() {
+            let (metrics, benchmark) = (&self.metrics, self.benchmark.as_ref());
+            self.worker.run_batched(|latency, result| {
+                record(metrics, benchmark, latency, result);
+            });
+        } else {
+            while let Ok(envelope) = self.worker.work_queue.recv() {
+                let start = Instant::now();
+                let result = self.worker.process_request(&envelope.request);
+                record(
+                    &self.metrics,
+                    self.benchmark.as_ref(),
+                    start.elapsed(),
+                    &result,
+                );
 
-            // Track benchmark if enabled
-            if let Some(ref benchmark) = self.benchmark {
-                benchmark.record_request(latency, result.success);
+                envelope.complete(result);
             }
-
-            let _ = response_tx.send(result);
         }
 
         println!("Worker {} shutting down", self.worker.worker_id);
     }
 }
 
+#[inline]
+fn record(
+    metrics: &MetricsCollector,
+    benchmark: Option<&BenchmarkCollector>,
+    latency: Duration,
+    result: &WorkResponse,
+) {
+    // Track metrics
+    if result.success {
+        metrics.record_success();
+        metrics.record_written(result.rows, result.bytes);
+    } else {
+        metrics.record_failure();
+    }
+
+    // Track benchmark if enabled
+    if let Some(benchmark) = benchmark {
+        benchmark.record_request(latency, result.success);
+    }
+}
+
 pub fn spawn_worker_pool(
     worker_count: usize,
-    db_pool: DbPool,
-    receiver: Receiver<(WorkRequest, Sender<WorkResponse>)>,
+    connections: ConnectionSource,
+    receiver: Receiver<WorkEnvelope>,
+    options: WorkerOptions,
 ) -> Vec<thread::JoinHandle<()>> {
     (0..worker_count)
         .map(|worker_id| {
             let rx = receiver.clone();
-            let pool = db_pool.clone();
+            let source = connections.clone();
 
             thread::Builder::new()
                 .name(format!("worker-{}", worker_id))
                 .spawn(move || {
-                    let mut worker = Worker::new(worker_id, pool, rx);
+                    let mut worker = Worker::new(worker_id, source, rx, None, options);
                     worker.run();
                 })
                 .expect("Failed to spawn worker thread")
@@ -169,23 +530,33 @@ pub fn spawn_worker_pool(
 
 pub fn spawn_worker_pool_with_metrics(
     worker_count: usize,
-    db_pool: DbPool,
-    receiver: Receiver<(WorkRequest, Sender<WorkResponse>)>,
+    connections: ConnectionSource,
+    receiver: Receiver<WorkEnvelope>,
     metrics: MetricsCollector,
     benchmark: Option<BenchmarkCollector>,
+    pool_metrics: Option<PoolMetricsCollector>,
+    options: WorkerOptions,
 ) -> Vec<thread::JoinHandle<()>> {
     (0..worker_count)
         .map(|worker_id| {
             let rx = receiver.clone();
-            let pool = db_pool.clone();
+            let source = connections.clone();
             let metrics_clone = metrics.clone_handle();
             let benchmark_clone = benchmark.as_ref().map(|b| b.clone_handle());
+            let pool_metrics_clone = pool_metrics.as_ref().map(|p| p.clone_handle());
 
             thread::Builder::new()
                 .name(format!("worker-{}", worker_id))
                 .spawn(move || {
-                    let mut worker =
-                        WorkerWithMetrics::new(worker_id, pool, rx, metrics_clone, benchmark_clone);
+                    let mut worker = WorkerWithMetrics::new(
+                        worker_id,
+                        source,
+                        rx,
+                        metrics_clone,
+                        benchmark_clone,
+                        pool_metrics_clone,
+                        options,
+                    );
                     worker.run();
                 })
                 .expect("Failed to spawn worker thread")
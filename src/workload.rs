@@ -0,0 +1,172 @@
+use serde::Deserialize;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// What a worker does with each request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WorkloadKind {
+    // One single-row INSERT per request
+    Insert,
+    // One `COPY FROM STDIN` of `copy_rows` generated rows per request
+    Copy,
+    // Up to `batch_size` requests per INSERT, one VALUES row and parameter each
+    MultiRow,
+    // Up to `batch_size` requests per INSERT, ids passed as one array through UNNEST
+    Unnest,
+}
+
+impl fmt::Display for WorkloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkloadKind::Insert => f.write_str("insert"),
+            WorkloadKind::Copy => f.write_str("copy"),
+            WorkloadKind::MultiRow => f.write_str("multi_row"),
+            WorkloadKind::Unnest => f.write_str("unnest"),
+        }
+    }
+}
+
+// Replayed traces name the kind per event, in any case
+impl FromStr for WorkloadKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "insert" => Ok(WorkloadKind::Insert),
+            "copy" => Ok(WorkloadKind::Copy),
+            "multi_row" => Ok(WorkloadKind::MultiRow),
+            "unnest" => Ok(WorkloadKind::Unnest),
+            _ => Err(format!("unknown operation {:?}", s)),
+        }
+    }
+}
+
+/// How a workload's statements are sent to the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum QueryMode {
+    // Named statement, parsed once per connection and SQL text, then only bound and run
+    Prepared,
+    // Unnamed statement, parsed and planned again on every execution
+    Unnamed,
+    // Simple query protocol with values inlined as literals
+    Simple,
+}
+
+impl fmt::Display for QueryMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QueryMode::Prepared => f.write_str("prepared"),
+            QueryMode::Unnamed => f.write_str("unnamed"),
+            QueryMode::Simple => f.write_str("simple"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct WorkloadSettings {
+    pub kinds: Vec<WorkloadKind>,
+    // Ignored by COPY, which has its own protocol
+    pub query_modes: Vec<QueryMode>,
+    // Rows streamed per COPY request
+    pub copy_rows: usize,
+    // Batched kinds send a batch once it holds this many requests, or once this long
+    // has passed since its first request was taken, whichever comes first
+    pub batch_size: usize,
+    pub batch_wait_ms: u64,
+}
+
+impl Default for WorkloadSettings {
+    fn default() -> Self {
+        Self {
+            kinds: vec![WorkloadKind::Insert],
+            query_modes: vec![QueryMode::Unnamed],
+            copy_rows: 100,
+            batch_size: 50,
+            batch_wait_ms: 5,
+        }
+    }
+}
+
+impl WorkloadSettings {
+    /// One workload per configured kind and query mode, in order.
+    pub fn workloads(&self) -> Vec<Workload> {
+        let modes = if self.query_modes.is_empty() {
+            &[QueryMode::Unnamed][..]
+        } else {
+            &self.query_modes[..]
+        };
+
+        self.kinds
+            .iter()
+            .flat_map(|&kind| {
+                let modes = match kind {
+                    WorkloadKind::Copy => &modes[..1],
+                    _ => modes,
+                };
+
+                modes.iter().map(move |&query_mode| Workload {
+                    kind,
+                    query_mode,
+                    copy_rows: self.copy_rows.max(1),
+                    batch_size: match kind {
+                        WorkloadKind::MultiRow | WorkloadKind::Unnest => self.batch_size.max(1),
+                        WorkloadKind::Insert | WorkloadKind::Copy => 1,
+                    },
+                    batch_wait: Duration::from_millis(self.batch_wait_ms),
+                })
+            })
+            .collect()
+    }
+}
+
+/// The workload a single run's workers execute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Workload {
+    pub kind: WorkloadKind,
+    // Ignored by COPY
+    pub query_mode: QueryMode,
+    pub copy_rows: usize,
+    // Always 1 for kinds that run each request on its own
+    pub batch_size: usize,
+    pub batch_wait: Duration,
+}
+
+impl Workload {
+    pub fn is_batched(&self) -> bool {
+        matches!(self.kind, WorkloadKind::MultiRow | WorkloadKind::Unnest)
+    }
+
+    /// Short run label; empty for the default single-row INSERT as an unnamed statement.
+    pub fn label(&self) -> String {
+        let mut parts = Vec::with_capacity(2);
+        if self.kind != WorkloadKind::Insert {
+            parts.push(self.kind.to_string());
+        }
+        if self.kind != WorkloadKind::Copy && self.query_mode != QueryMode::Unnamed {
+            parts.push(self.query_mode.to_string());
+        }
+
+        parts.join("/")
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            WorkloadKind::Insert => write!(f, "{} ({})", self.kind, self.query_mode),
+            WorkloadKind::Copy => write!(f, "{} x{}", self.kind, self.copy_rows),
+            WorkloadKind::MultiRow | WorkloadKind::Unnest => write!(
+                f,
+                "{} x{} / {}ms ({})",
+                self.kind,
+                self.batch_size,
+                self.batch_wait.as_millis(),
+                self.query_mode
+            ),
+        }
+    }
+}